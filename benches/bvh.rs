@@ -0,0 +1,81 @@
+// Compares rendering the final_render scene with a flat list of shapes against the BVH.
+//
+// Run with `cargo bench --bench bvh`.
+
+use std::time::{Duration, Instant};
+
+use glam::{vec3, Vec3};
+use itertools::Itertools;
+use rand::prelude::*;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::CameraBuilder;
+use ray_tracing::hittable::*;
+use ray_tracing::material::Material::*;
+
+fn random_color(rng: &mut StdRng) -> Vec3 {
+    vec3(rng.gen(), rng.gen(), rng.gen())
+}
+
+// Same layout as examples/final_render.rs, but generated from a fixed seed.
+fn final_render_scene() -> Vec<Shape> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut world = (-11..11).cartesian_product(-11..11)
+        .map(|(a, b)| vec3(a as f32 + 0.9 * rng.gen::<f32>(), 0.2, b as f32 + 0.9 * rng.gen::<f32>()))
+        .filter(|&center| (center - vec3(4.0, 0.2, 0.0)).length() > 0.9)
+        .collect::<Vec<Vec3>>()
+        .into_iter()
+        .map(|center| {
+            let choose_mat = rng.gen::<f32>();
+            let material = if choose_mat < 0.8 {
+                Lambertian((random_color(&mut rng) * random_color(&mut rng)).into())
+            } else if choose_mat < 0.95 {
+                Metal(random_color(&mut rng).into(), rng.gen_range(0.0..0.5))
+            } else {
+                Dielectric(1.5.into())
+            };
+            Shape::new_sphere(center, 0.2, &material)
+        })
+        .collect::<Vec<Shape>>();
+
+    world.push(Shape::new_sphere(vec3(0.0, -1000.0, 0.0), 1000.0, &Lambertian(vec3(0.5, 0.5, 0.5).into())));
+    world.push(Shape::new_sphere(vec3(0.0, 1.0, 0.0), 1.0, &Dielectric(1.5.into())));
+    world.push(Shape::new_sphere(vec3(-4.0, 1.0, 0.0), 1.0, &Lambertian(vec3(0.4, 0.2, 0.1).into())));
+    world.push(Shape::new_sphere(vec3(4.0, 1.0, 0.0), 1.0, &Metal(vec3(0.7, 0.6, 0.5).into(), 0.0)));
+    world
+}
+
+fn time<F: FnOnce()>(f: F) -> Duration {
+    let start = Instant::now();
+    f();
+    start.elapsed()
+}
+
+fn main() {
+    let camera = CameraBuilder::default()
+        .set_image_width(200)
+        .set_samples_per_pixel(8)
+        .set_max_depth(50)
+        .set_vfov(20.0)
+        .set_view_direction(vec3(13.0, 2.0, 3.0), vec3(0.0, 0.0, 0.0))
+        .set_focus(0.6, 10.0)
+        .build();
+
+    let flat = final_render_scene();
+    println!("final_render scene: {} shapes", flat.len());
+
+    let mut flat_image = None;
+    let flat_time = time(|| flat_image = Some(camera.render(&flat)));
+
+    let mut bvh = None;
+    let shapes = final_render_scene();
+    let build_time = time(|| bvh = Some(Bvh::new(shapes)));
+    let bvh = bvh.expect("Built above.");
+    let mut bvh_image = None;
+    let bvh_time = time(|| bvh_image = Some(camera.render(&bvh)));
+    assert!(flat_image == bvh_image, "The BVH should render the same image as the flat list.");
+
+    println!("flat list: {:>10.3?}", flat_time);
+    println!("bvh build: {:>10.3?}", build_time);
+    println!("bvh:       {:>10.3?}", bvh_time);
+    println!("speedup:   {:>9.1}x", flat_time.as_secs_f64() / bvh_time.as_secs_f64());
+}
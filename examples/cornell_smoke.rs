@@ -0,0 +1,51 @@
+use glam::{vec3, Affine3A, Vec3};
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+
+fn main() {
+    // Materials
+    let red   = Lambertian(Color::new(0.65, 0.05, 0.05).into());
+    let white = Lambertian(Color::new(0.73, 0.73, 0.73).into());
+    let green = Lambertian(Color::new(0.12, 0.45, 0.15).into());
+    let light = DiffuseLight(Color::new(7.0, 7.0, 7.0));
+
+    // World
+    let light_quad = Shape::new_quad(vec3(113.0, 554.0, 127.0), vec3(330.0, 0.0, 0.0), vec3(0.0, 0.0, 305.0), &light);
+    let mut world = vec![
+        Shape::new_quad(vec3(555.0, 0.0, 0.0), vec3(0.0, 555.0, 0.0), vec3(0.0, 0.0, 555.0), &green),
+        Shape::new_quad(vec3(0.0, 0.0, 0.0), vec3(0.0, 555.0, 0.0), vec3(0.0, 0.0, 555.0), &red),
+        light_quad.clone(),
+        Shape::new_quad(vec3(0.0, 555.0, 0.0), vec3(555.0, 0.0, 0.0), vec3(0.0, 0.0, 555.0), &white),
+        Shape::new_quad(vec3(0.0, 0.0, 0.0), vec3(555.0, 0.0, 0.0), vec3(0.0, 0.0, 555.0), &white),
+        Shape::new_quad(vec3(0.0, 0.0, 555.0), vec3(555.0, 0.0, 0.0), vec3(0.0, 555.0, 0.0), &white),
+    ];
+
+    // Blocks of white and black smoke
+    let tall_box = Shape::new_box(vec3(0.0, 0.0, 0.0), vec3(165.0, 330.0, 165.0), &white);
+    let tall_box = Shape::new_instance(tall_box, rotate_y_then_translate(15.0, vec3(265.0, 0.0, 295.0)));
+    let short_box = Shape::new_box(vec3(0.0, 0.0, 0.0), vec3(165.0, 165.0, 165.0), &white);
+    let short_box = Shape::new_instance(short_box, rotate_y_then_translate(-18.0, vec3(130.0, 0.0, 65.0)));
+    world.push(Shape::new_constant_medium(vec![tall_box], 0.01, Color::ZERO.into()));
+    world.push(Shape::new_constant_medium(vec![short_box], 0.01, Color::ONE.into()));
+
+    let camera = CameraBuilder::default()
+        .set_aspect_ratio(1.0)
+        .set_image_width(600)
+        .set_samples_per_pixel(200)
+        .set_max_depth(50)
+        .set_background(Color::ZERO)
+        .set_vfov(40.0)
+        .set_view_direction(vec3(278.0, 278.0, -800.0), vec3(278.0, 278.0, 0.0))
+        .set_lights(vec![light_quad])
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
+
+fn rotate_y_then_translate(degrees: f32, offset: Vec3) -> Affine3A {
+    Affine3A::from_translation(offset) * Affine3A::from_rotation_y(degrees.to_radians())
+}
@@ -0,0 +1,56 @@
+use glam::{vec3, Affine3A};
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::csg::CsgOperation::*;
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+use ray_tracing::texture::Texture;
+
+// A glass lens made from two spheres, a ball with a hole drilled through it and a cube with
+// a spherical bite taken out. Cut surfaces show the material of the shape that cut them.
+fn main() {
+    let checker = Texture::checker(0.5, Color::new(0.2, 0.3, 0.1).into(), Color::splat(0.9).into());
+    let ground = Lambertian(checker);
+    let glass = Dielectric(1.5.into());
+    let red = Lambertian(Color::new(0.7, 0.15, 0.1).into());
+    let gold = Metal(Color::new(0.8, 0.6, 0.2).into(), 0.2);
+    let blue = Lambertian(Color::new(0.1, 0.25, 0.7).into());
+
+    // Where two large spheres overlap
+    let lens = Shape::new_csg(
+        Intersection,
+        vec![Shape::new_sphere(vec3(-3.0, 1.1, -1.6), 2.0, &glass)],
+        vec![Shape::new_sphere(vec3(-3.0, 1.1, 1.6), 2.0, &glass)]);
+
+    let drill = Shape::new_cylinder(vec3(0.0, -1.5, 0.0), vec3(0.0, 1.5, 0.0), 0.4, &gold).transformed(
+        Affine3A::from_translation(vec3(0.0, 1.0, 0.0))
+            * Affine3A::from_rotation_z(30f32.to_radians())
+            * Affine3A::from_rotation_x(60f32.to_radians()));
+    let drilled_ball = Shape::new_csg(Difference, vec![Shape::new_sphere(vec3(0.0, 1.0, 0.0), 1.0, &red)], vec![drill]);
+
+    let bitten_cube = Shape::new_csg(
+        Difference,
+        Shape::new_box(vec3(2.0, 0.0, -0.9), vec3(3.8, 1.8, 0.9), &blue),
+        vec![Shape::new_sphere(vec3(3.8, 1.8, 0.9), 1.0, &gold)]);
+    let cube_with_ball = Shape::new_csg(Union, vec![bitten_cube], vec![Shape::new_sphere(vec3(2.9, 1.8, 0.0), 0.5, &gold)]);
+
+    let world = vec![
+        Shape::new_plane(vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), &ground),
+        lens,
+        drilled_ball,
+        cube_with_ball,
+    ];
+
+    let camera = CameraBuilder::default()
+        .set_aspect_ratio(2.0)
+        .set_image_width(600)
+        .set_samples_per_pixel(64)
+        .set_max_depth(16)
+        .set_vfov(30.0)
+        .set_view_direction(vec3(0.0, 3.5, 10.0), vec3(0.0, 0.9, 0.0))
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -0,0 +1,53 @@
+use glam::vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::{Ior, Material::*};
+
+// Thin white light strips seen through a flint glass sphere and prism. Blue light bends
+// more than red, so the edges of the strips get colored fringes in the spectral render.
+fn main() {
+    let floor = Lambertian(Color::splat(0.2).into());
+    let flint = Dielectric(Ior::Cauchy { a: 1.6, b: 0.05 });
+    let light = DiffuseLight(Color::splat(8.0));
+
+    let strips = (0..6)
+        .map(|i| {
+            let x = -3.55 + 1.5 * i as f32;
+            Shape::new_quad(vec3(x, 0.0, -3.0), vec3(0.1, 0.0, 0.0), vec3(0.0, 4.0, 0.0), &light)
+        })
+        .collect::<Vec<Shape>>();
+
+    let mut world = vec![
+        Shape::new_quad(vec3(-20.0, 0.0, -20.0), vec3(40.0, 0.0, 0.0), vec3(0.0, 0.0, 40.0), &floor),
+        Shape::new_sphere(vec3(-1.5, 1.2, 0.0), 1.2, &flint),
+    ];
+    world.extend(strips.iter().cloned());
+
+    // Prism with an equilateral cross section, standing on the floor
+    let (a, b, c) = (vec3(1.0, 0.0, 0.46), vec3(2.6, 0.0, 0.46), vec3(1.8, 0.0, -0.92));
+    let up = vec3(0.0, 2.4, 0.0);
+    world.extend([
+        Shape::new_triangle(a, b, c, &flint),
+        Shape::new_triangle(a + up, b + up, c + up, &flint),
+        Shape::new_quad(a, b - a, up, &flint),
+        Shape::new_quad(b, c - b, up, &flint),
+        Shape::new_quad(c, a - c, up, &flint),
+    ]);
+
+    let camera = CameraBuilder::default()
+        .set_aspect_ratio(2.0)
+        .set_image_width(600)
+        .set_samples_per_pixel(256)
+        .set_max_depth(16)
+        .set_background(Color::ZERO)
+        .set_vfov(30.0)
+        .set_view_direction(vec3(0.0, 1.5, 9.0), vec3(0.0, 1.2, 0.0))
+        .set_lights(strips)
+        .set_spectral(true)
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -0,0 +1,38 @@
+use glam::vec3;
+use ray_tracing::background::Background;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+
+// Spheres lit by an HDR environment map: `cargo run --release --example environment -- map.hdr`
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "assets/studio.hdr".to_string());
+    let background = Background::environment_map(&path).expect("Should be able to load the environment map.");
+
+    // Materials
+    let material_ground = Lambertian(Color::new(0.5, 0.5, 0.5).into());
+    let material_left   = Metal(Color::new(0.9, 0.9, 0.9).into(), 0.0);
+    let material_center = Lambertian(Color::new(0.7, 0.3, 0.3).into());
+    let material_right  = Dielectric(1.5.into());
+
+    // World
+    let world = vec![
+        Shape::new_sphere(vec3( 0.0, -100.5, -1.0), 100.0, &material_ground),
+        Shape::new_sphere(vec3(-1.0,    0.0, -1.0),   0.5, &material_left),
+        Shape::new_sphere(vec3( 0.0,    0.0, -1.0),   0.5, &material_center),
+        Shape::new_sphere(vec3( 1.0,    0.0, -1.0),   0.5, &material_right),
+    ];
+
+    let camera = CameraBuilder::default()
+        .set_samples_per_pixel(100)
+        .set_max_depth(50)
+        .set_background(background)
+        .set_vfov(50.0)
+        .set_view_direction(vec3(0.0, 0.5, 1.5), vec3(0.0, 0.0, -1.0))
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -2,7 +2,9 @@
 use itertools::Itertools;
 use rand::Rng;
 use ray_tracing::material::{Material::*, random_vec};
+use ray_tracing::bvh::Bvh;
 use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
 use ray_tracing::camera::CameraBuilder;
 use glam::vec3;
 
@@ -14,39 +16,38 @@ fn main() {
     .map(|(a, b)| {
         let a = a as f32;
         let b = b as f32;
-        let center = vec3(a + 0.9 * rand::thread_rng().gen::<f32>(), 0.2, b + 0.9*rand::thread_rng().gen::<f32>());
-        center
+        vec3(a + 0.9 * rand::thread_rng().gen::<f32>(), 0.2, b + 0.9*rand::thread_rng().gen::<f32>())
     })
     .filter(|&center| ( center - vec3(4.0, 0.2, 0.0)).length() > 0.9)
     .map(|center| {  
             let choose_mat = rand::thread_rng().gen::<f32>();
             let sphere_material = if choose_mat < 0.8 {
                 // diffuse
-                let albedo = (random_vec() * random_vec()).abs(); // OBS super important that all elements of the Color vec are positive
-                Lambertian(albedo)
+                let albedo = (random_vec(&mut rand::thread_rng()) * random_vec(&mut rand::thread_rng())).abs(); // OBS super important that all elements of the Color vec are positive
+                Lambertian(albedo.into())
             } else if choose_mat < 0.95 {
                 // metal
-                let albedo = random_vec().abs();
+                let albedo = random_vec(&mut rand::thread_rng()).abs();
                 let fuzz = rand::thread_rng().gen_range(0.0..0.5);
-                Metal(albedo, fuzz)
+                Metal(albedo.into(), fuzz)
             } else {
                 // glass
-                Dielectric(1.5)
+                Dielectric(1.5.into())
             };
             Shape::new_sphere(center, 0.2, &sphere_material)
         })
         .collect::<Vec<Shape>>();
     
-    let ground_material = Lambertian(vec3(0.5, 0.5, 0.5));
+    let ground_material = Lambertian(vec3(0.5, 0.5, 0.5).into());
     world.push(Shape::new_sphere(vec3(0.0,-1000.0,0.0), 1000.0, &ground_material));
 
-    let material1 = Dielectric(1.5);
+    let material1 = Dielectric(1.5.into());
     world.push(Shape::new_sphere(vec3(0.0, 1.0, 0.0), 1.0, &material1));
 
-    let material2 = Lambertian(vec3(0.4, 0.2, 0.1));
+    let material2 = Lambertian(vec3(0.4, 0.2, 0.1).into());
     world.push(Shape::new_sphere(vec3(-4.0, 1.0, 0.0), 1.0, &material2));
 
-    let material3 = Metal(vec3(0.7, 0.6, 0.5), 0.0);
+    let material3 = Metal(vec3(0.7, 0.6, 0.5).into(), 0.0);
     world.push(Shape::new_sphere(vec3(4.0, 1.0, 0.0), 1.0, &material3));
 
     let camera = CameraBuilder::default()
@@ -58,5 +59,6 @@ fn main() {
         .set_focus(0.6, 10.0)
         .build();
 
-    camera.render(world);
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
 }
\ No newline at end of file
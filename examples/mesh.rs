@@ -0,0 +1,45 @@
+use glam::vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+use ray_tracing::mesh::Mesh;
+
+// Renders an OBJ model standing on a floor: `cargo run --release --example mesh -- model.obj`
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "assets/icosphere.obj".to_string());
+
+    // Materials
+    let material_model = Metal(Color::new(0.8, 0.6, 0.2).into(), 0.2);
+    let material_floor = Lambertian(Color::new(0.5, 0.5, 0.5).into());
+
+    let mesh = Mesh::load_obj(&path, &material_model).expect("Should be able to load the model.");
+    println!("{}: {} triangles", path, mesh.triangles().len());
+
+    // Frame the camera and the floor around the model.
+    let bbox = mesh.bounding_box();
+    let center = bbox.centroid();
+    let size = (bbox.max() - bbox.min()).max_element();
+    let floor_y = bbox.y.min;
+
+    // World
+    let world = vec![
+        Shape::new_mesh(mesh),
+        Shape::new_quad(
+            vec3(center.x - 5.0 * size, floor_y, center.z + 5.0 * size),
+            vec3(10.0 * size, 0.0, 0.0),
+            vec3(0.0, 0.0, -10.0 * size),
+            &material_floor),
+    ];
+
+    let camera = CameraBuilder::default()
+        .set_samples_per_pixel(64)
+        .set_max_depth(32)
+        .set_vfov(40.0)
+        .set_view_direction(center + size * vec3(0.6, 0.6, 1.8), center)
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -0,0 +1,41 @@
+use ray_tracing::material::Material::*;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::camera::CameraBuilder;
+use ray_tracing::texture::Texture;
+use glam::vec3;
+
+// Small spheres bouncing up while the shutter is open, next to three still ones.
+fn main() {
+    // World
+    let checker = Texture::checker(0.32, vec3(0.2, 0.3, 0.1).into(), vec3(0.9, 0.9, 0.9).into());
+    let ground = Lambertian(checker);
+    let red = Lambertian(vec3(0.7, 0.2, 0.1).into());
+    let blue = Lambertian(vec3(0.1, 0.3, 0.7).into());
+    let yellow = Lambertian(vec3(0.8, 0.7, 0.2).into());
+    let glass = Dielectric(1.5.into());
+    let steel = Metal(vec3(0.7, 0.6, 0.5).into(), 0.0);
+
+    let world = vec![
+        Shape::new_sphere(vec3(0.0, -1000.0, 0.0), 1000.0, &ground),
+        Shape::new_moving_sphere(vec3(-1.0, 0.3, 2.0), vec3(-1.0, 0.7, 2.0), 0.3, &red),
+        Shape::new_moving_sphere(vec3(1.5, 0.3, 1.5), vec3(1.5, 0.5, 1.5), 0.3, &blue),
+        Shape::new_moving_sphere(vec3(6.0, 0.25, 2.0), vec3(6.0, 0.6, 2.0), 0.25, &yellow),
+        Shape::new_sphere(vec3(0.0, 1.0, 0.0), 1.0, &glass),
+        Shape::new_sphere(vec3(-4.0, 1.0, 0.0), 1.0, &red),
+        Shape::new_sphere(vec3(4.0, 1.0, 0.0), 1.0, &steel),
+    ];
+
+    let camera = CameraBuilder::default()
+        .set_image_width(800)
+        .set_samples_per_pixel(128)
+        .set_max_depth(50)
+        .set_vfov(20.0)
+        .set_view_direction(vec3(13.0, 2.0, 3.0), vec3(0.0, 0.0, 0.0))
+        .set_shutter(0.0, 1.0)
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
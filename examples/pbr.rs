@@ -0,0 +1,34 @@
+use glam::vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+use ray_tracing::texture::Texture;
+
+// Rows of microfacet spheres getting rougher to the right: gold in the back, red plastic
+// in the front.
+fn main() {
+    let checker = Texture::checker(0.5, Color::splat(0.2).into(), Color::splat(0.8).into());
+    let mut world = vec![Shape::new_sphere(vec3(0.0, -1000.0, 0.0), 1000.0, &Lambertian(checker))];
+
+    let gold = Color::new(1.0, 0.78, 0.34);
+    let plastic = Color::new(0.7, 0.1, 0.1);
+    for (i, roughness) in [0.05, 0.3, 0.55, 0.8].into_iter().enumerate() {
+        let x = -3.0 + 2.0 * i as f32;
+        world.push(Shape::new_sphere(vec3(x, 0.6, -1.5), 0.6, &Microfacet(gold.into(), roughness, 1.0)));
+        world.push(Shape::new_sphere(vec3(x, 0.6, 1.5), 0.6, &Microfacet(plastic.into(), roughness, 0.0)));
+    }
+
+    let camera = CameraBuilder::default()
+        .set_aspect_ratio(2.0)
+        .set_image_width(600)
+        .set_samples_per_pixel(64)
+        .set_max_depth(16)
+        .set_vfov(30.0)
+        .set_view_direction(vec3(0.0, 5.0, 9.0), vec3(0.0, 0.3, 0.0))
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -1,23 +1,25 @@
 use ray_tracing::material::Material::*;
+use ray_tracing::bvh::Bvh;
 use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
 use ray_tracing::camera::CameraBuilder;
 use glam::vec3;
 
 fn main() {
-    // World
-    let mut world: Vec<Shape> = Vec::new();
-
     // Materials
-    let material_ground = Lambertian(vec3(0.8, 0.8, 0.0));
-    let material_center = Lambertian(vec3(0.1, 0.2, 0.5));
-    let material_left =  Dielectric(1.5);
-    let material_right  = Metal(vec3(0.8, 0.6, 0.2), 0.0);
+    let material_ground = Lambertian(vec3(0.8, 0.8, 0.0).into());
+    let material_center = Lambertian(vec3(0.1, 0.2, 0.5).into());
+    let material_left =  Dielectric(1.5.into());
+    let material_right  = Metal(vec3(0.8, 0.6, 0.2).into(), 0.0);
 
-    world.push(Shape::new_sphere(vec3( 0.0, -100.5, -1.0), 100.0, &material_ground));
-    world.push(Shape::new_sphere(vec3( 0.0,    0.0, -1.0),   0.5, &material_center));
-    world.push(Shape::new_sphere(vec3(-1.0,    0.0, -1.0),   0.5, &material_left));
-    world.push(Shape::new_sphere(vec3(-1.0,    0.0, -1.0),  -0.4, &material_left));
-    world.push(Shape::new_sphere(vec3( 1.0,    0.0, -1.0),   0.5, &material_right));
+    // World
+    let world = vec![
+        Shape::new_sphere(vec3( 0.0, -100.5, -1.0), 100.0, &material_ground),
+        Shape::new_sphere(vec3( 0.0,    0.0, -1.0),   0.5, &material_center),
+        Shape::new_sphere(vec3(-1.0,    0.0, -1.0),   0.5, &material_left),
+        Shape::new_sphere(vec3(-1.0,    0.0, -1.0),  -0.4, &material_left),
+        Shape::new_sphere(vec3( 1.0,    0.0, -1.0),   0.5, &material_right),
+    ];
 
     let camera = CameraBuilder::default()
         .set_samples_per_pixel(200)
@@ -27,5 +29,6 @@ fn main() {
         .set_focus(11.0, 3.4)
         .build();
 
-    camera.render(world);
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
 }
\ No newline at end of file
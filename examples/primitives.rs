@@ -0,0 +1,40 @@
+use glam::vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+use ray_tracing::texture::Texture;
+
+// A cylinder, a cone, a torus, a glass cylinder on its side and a disk on an infinite plane.
+fn main() {
+    let checker = Texture::checker(0.5, Color::new(0.2, 0.3, 0.1).into(), Color::splat(0.9).into());
+    let ground = Lambertian(checker);
+    let red = Lambertian(Color::new(0.7, 0.15, 0.1).into());
+    let blue = Lambertian(Color::new(0.1, 0.25, 0.7).into());
+    let gold = Metal(Color::new(0.8, 0.6, 0.2).into(), 0.1);
+    let glass = Dielectric(1.5.into());
+    let white = Lambertian(Color::splat(0.8).into());
+
+    // The plane has no bounds; the Bvh tests it for every ray.
+    let world = vec![
+        Shape::new_plane(vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), &ground),
+        Shape::new_cylinder(vec3(-3.0, 0.0, 0.0), vec3(-3.0, 1.6, 0.0), 0.6, &red),
+        Shape::new_cone(vec3(-1.0, 0.0, 0.0), vec3(-1.0, 2.0, 0.0), 0.7, &blue),
+        Shape::new_torus(vec3(1.2, 0.8, 0.0), vec3(0.0, 0.6, 1.0), 0.6, 0.25, &gold),
+        Shape::new_cylinder(vec3(2.6, 0.45, 0.5), vec3(3.6, 0.45, -0.8), 0.45, &glass),
+        Shape::new_disk(vec3(0.0, 0.01, 2.0), vec3(0.0, 1.0, 0.0), 0.6, &white),
+    ];
+
+    let camera = CameraBuilder::default()
+        .set_aspect_ratio(2.0)
+        .set_image_width(600)
+        .set_samples_per_pixel(64)
+        .set_max_depth(16)
+        .set_vfov(30.0)
+        .set_view_direction(vec3(0.0, 4.0, 10.0), vec3(0.0, 0.8, 0.0))
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use ray_tracing::bvh::Bvh;
+use ray_tracing::image_io::save;
+use ray_tracing::progressive::Convergence;
+use ray_tracing::scene::read_scene;
+
+// Renders the Cornell box progressively, rewriting image.png after every pass, until the
+// noise target, 1024 samples per pixel or a one minute budget is reached.
+fn main() {
+    let (mut camera, world) = read_scene("scenes/cornell_box.scene").expect("Should be able to load the scene.");
+    let camera = camera.set_image_width(300).set_samples_per_pixel(1024).build();
+
+    let convergence = Convergence {
+        samples_per_pass: 8,
+        min_samples: 32,
+        target_noise: Some(0.05),
+        time_budget: Some(Duration::from_secs(60)),
+    };
+
+    camera.render_progressive(&Bvh::new(world), &convergence, |report| {
+        println!(
+            "pass {:>3}: {:>10} samples, {:>6} pixels still sampling, {:.1?}",
+            report.pass, report.total_samples, report.active_pixels, report.elapsed);
+        save(report.image, "image.png").expect("Should be able to write the image.");
+    });
+}
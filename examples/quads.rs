@@ -0,0 +1,37 @@
+use glam::vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+
+fn main() {
+    // Materials
+    let left_red     = Lambertian(Color::new(1.0, 0.2, 0.2).into());
+    let back_green   = Lambertian(Color::new(0.2, 1.0, 0.2).into());
+    let right_blue   = Lambertian(Color::new(0.2, 0.2, 1.0).into());
+    let upper_orange = Lambertian(Color::new(1.0, 0.5, 0.0).into());
+    let lower_teal   = Lambertian(Color::new(0.2, 0.8, 0.8).into());
+    let box_white    = Metal(Color::new(0.8, 0.8, 0.8).into(), 0.05);
+
+    // World
+    let mut world = vec![
+        Shape::new_quad(vec3(-3.0, -2.0, 5.0), vec3(0.0, 0.0, -4.0), vec3(0.0, 4.0, 0.0), &left_red),
+        Shape::new_quad(vec3(-2.0, -2.0, 0.0), vec3(4.0, 0.0, 0.0), vec3(0.0, 4.0, 0.0), &back_green),
+        Shape::new_quad(vec3( 3.0, -2.0, 1.0), vec3(0.0, 0.0, 4.0), vec3(0.0, 4.0, 0.0), &right_blue),
+        Shape::new_quad(vec3(-2.0,  3.0, 1.0), vec3(4.0, 0.0, 0.0), vec3(0.0, 0.0, 4.0), &upper_orange),
+        Shape::new_quad(vec3(-2.0, -3.0, 5.0), vec3(4.0, 0.0, 0.0), vec3(0.0, 0.0, -4.0), &lower_teal),
+    ];
+    world.extend(Shape::new_box(vec3(-0.5, -0.5, 2.5), vec3(0.5, 0.5, 3.5), &box_white));
+
+    let camera = CameraBuilder::default()
+        .set_aspect_ratio(1.0)
+        .set_samples_per_pixel(100)
+        .set_max_depth(50)
+        .set_vfov(80.0)
+        .set_view_direction(vec3(0.0, 0.0, 9.0), vec3(0.0, 0.0, 0.0))
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -0,0 +1,35 @@
+use glam::vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::*;
+use ray_tracing::texture::Texture;
+
+fn main() {
+    let mut rng = rand::thread_rng();
+
+    // Textures
+    let checker = Texture::checker(0.32, Color::new(0.2, 0.3, 0.1).into(), Color::new(0.9, 0.9, 0.9).into());
+    let marble = Texture::marble(4.0, &mut rng);
+    let noise = Texture::noise(8.0, &mut rng);
+    let environment = Texture::image("assets/studio.hdr").expect("Should be able to load the image.");
+
+    // World
+    let world = vec![
+        Shape::new_sphere(vec3( 0.0, -1000.0, 0.0), 1000.0, &Lambertian(checker)),
+        Shape::new_sphere(vec3(-2.2,     1.0, 0.0),    1.0, &Lambertian(marble)),
+        Shape::new_sphere(vec3( 0.0,     1.0, 0.0),    1.0, &Metal(noise, 0.3)),
+        Shape::new_sphere(vec3( 2.2,     1.0, 0.0),    1.0, &Lambertian(environment)),
+    ];
+
+    let camera = CameraBuilder::default()
+        .set_samples_per_pixel(100)
+        .set_max_depth(50)
+        .set_vfov(30.0)
+        .set_view_direction(vec3(0.0, 3.0, 12.0), vec3(0.0, 0.8, 0.0))
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -0,0 +1,37 @@
+use glam::vec3;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Color, CameraBuilder};
+use ray_tracing::hittable::*;
+use ray_tracing::image_io::save;
+use ray_tracing::material::Material::{self, *};
+use ray_tracing::texture::Texture;
+
+// Green glass of growing thickness: the bigger spheres and the thicker slab look darker.
+fn main() {
+    let checker = Texture::checker(0.5, Color::splat(0.2).into(), Color::splat(0.9).into());
+    let ground = Lambertian(checker);
+    // 50% of the red light is left after one unit of glass
+    let green = Material::tinted_glass(1.5, Color::new(0.5, 0.85, 0.6), 1.0);
+
+    let mut world = vec![
+        Shape::new_sphere(vec3(0.0, -1000.0, 0.0), 1000.0, &ground),
+        Shape::new_sphere(vec3(-3.0, 0.25, 0.0), 0.25, &green),
+        Shape::new_sphere(vec3(-1.6, 0.5, 0.0), 0.5, &green),
+        Shape::new_sphere(vec3(0.3, 0.8, 0.0), 0.8, &green),
+        Shape::new_sphere(vec3(2.6, 1.1, 0.0), 1.1, &green),
+    ];
+    world.extend(Shape::new_box(vec3(-3.5, 0.0, 1.5), vec3(-0.5, 0.1, 2.0), &green));
+    world.extend(Shape::new_box(vec3(0.5, 0.0, 1.5), vec3(3.5, 0.6, 2.0), &green));
+
+    let camera = CameraBuilder::default()
+        .set_aspect_ratio(2.0)
+        .set_image_width(600)
+        .set_samples_per_pixel(64)
+        .set_max_depth(32)
+        .set_vfov(30.0)
+        .set_view_direction(vec3(0.0, 2.5, 8.0), vec3(0.0, 0.6, 0.0))
+        .build();
+
+    let image = camera.render(&Bvh::new(world));
+    save(&image, "image.png").expect("Should be able to write the image.");
+}
@@ -0,0 +1,111 @@
+use glam::Vec3;
+use crate::interval::Interval;
+use crate::ray::{Point, Ray};
+
+// Axis-aligned bounding box, stored as one interval per axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+impl Aabb {
+    pub const EMPTY: Aabb = Aabb { x: Interval::EMPTY, y: Interval::EMPTY, z: Interval::EMPTY };
+    // Bounds of unbounded shapes such as planes.
+    pub const INFINITE: Aabb = Aabb { x: Interval::UNIVERSE, y: Interval::UNIVERSE, z: Interval::UNIVERSE };
+
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Aabb {
+        Aabb { x, y, z }
+    }
+
+    // Box spanned by two opposite corners, given in any order.
+    pub fn from_points(a: Point, b: Point) -> Aabb {
+        Aabb {
+            x: Interval::new(a.x.min(b.x), a.x.max(b.x)),
+            y: Interval::new(a.y.min(b.y), a.y.max(b.y)),
+            z: Interval::new(a.z.min(b.z), a.z.max(b.z)),
+        }
+    }
+
+    pub fn enclosing(a: Aabb, b: Aabb) -> Aabb {
+        Aabb {
+            x: Interval::enclosing(a.x, b.x),
+            y: Interval::enclosing(a.y, b.y),
+            z: Interval::enclosing(a.z, b.z),
+        }
+    }
+
+    pub fn overlap(a: Aabb, b: Aabb) -> Aabb {
+        Aabb {
+            x: Interval::overlap(a.x, b.x),
+            y: Interval::overlap(a.y, b.y),
+            z: Interval::overlap(a.z, b.z),
+        }
+    }
+
+    pub fn axis(&self, n: usize) -> Interval {
+        match n {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+
+    pub fn min(&self) -> Point {
+        Point::new(self.x.min, self.y.min, self.z.min)
+    }
+
+    pub fn max(&self) -> Point {
+        Point::new(self.x.max, self.y.max, self.z.max)
+    }
+
+    pub fn centroid(&self) -> Point {
+        0.5 * (self.min() + self.max())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.min > self.x.max || self.y.min > self.y.max || self.z.min > self.z.max
+    }
+
+    // Whether the box has finite bounds (empty boxes count as bounded).
+    pub fn is_bounded(&self) -> bool {
+        self.is_empty() || (self.min().is_finite() && self.max().is_finite())
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let d: Vec3 = self.max() - self.min();
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    // Pads flat boxes (e.g. of planar shapes) so that the slab test never divides a zero-width box.
+    pub fn pad(&self) -> Aabb {
+        let delta = 0.0001;
+        let pad_axis = |i: Interval| if i.size() < delta { i.expand(delta) } else { i };
+        Aabb { x: pad_axis(self.x), y: pad_axis(self.y), z: pad_axis(self.z) }
+    }
+
+    // Slab test. Returns the distance at which the ray enters the box, if it does so within the interval.
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<f32> {
+        let mut t_min = interval.min;
+        let mut t_max = interval.max;
+        for a in 0..3 {
+            let axis = self.axis(a);
+            let inv_d = 1.0 / ray.dir[a];
+            let t0 = (axis.min - ray.orig[a]) * inv_d;
+            let t1 = (axis.max - ray.orig[a]) * inv_d;
+            let (t0, t1) = if inv_d < 0.0 { (t1, t0) } else { (t0, t1) };
+
+            // Written so that NaN (0 * inf) leaves the bounds untouched.
+            if t0 > t_min { t_min = t0 }
+            if t1 < t_max { t_max = t1 }
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
+}
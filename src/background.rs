@@ -0,0 +1,73 @@
+use std::f32::consts::PI;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use glam::vec3;
+use crate::camera::Color;
+use crate::image::Image;
+use crate::image_io;
+use crate::ray::Ray;
+
+// What a ray sees when it leaves the scene without hitting anything.
+#[derive(Clone, Debug)]
+pub enum Background {
+    Solid(Color),
+    // Vertical blend from bottom (straight down) to top (straight up).
+    Gradient {
+        bottom: Color,
+        top: Color,
+    },
+    // Equirectangular environment map. rotation (degrees) turns it around the y axis,
+    // and intensity scales its radiance.
+    EnvironmentMap {
+        image: Arc<Image>,
+        rotation: f32,
+        intensity: f32,
+    },
+}
+
+impl Background {
+    // The white to light blue sky.
+    pub fn sky() -> Background {
+        Background::Gradient { bottom: vec3(1.0, 1.0, 1.0), top: vec3(0.5, 0.7, 1.0) }
+    }
+
+    // Loads an environment map from an .hdr, .pfm or .ppm file.
+    pub fn environment_map<P: AsRef<Path>>(path: P) -> io::Result<Background> {
+        let image = image_io::load(path)?;
+        Ok(Background::EnvironmentMap { image: Arc::new(image), rotation: 0.0, intensity: 1.0 })
+    }
+
+    pub fn color(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient { bottom, top } => {
+                let unit_direction = ray.dir.normalize();
+                let a = 0.5 * (unit_direction.y + 1.0);
+                bottom.lerp(*top, a)
+            }
+            Background::EnvironmentMap { image, rotation, intensity } => {
+                let d = ray.dir.normalize();
+                // Same convention as sphere UVs: u goes around from -x, v from the bottom up.
+                let theta = (-d.y).clamp(-1.0, 1.0).acos();
+                let phi = (-d.z).atan2(d.x) + PI;
+                let u = phi / (2.0 * PI) + rotation / 360.0;
+                let v = theta / PI;
+                *intensity * image.sample(u, v)
+            }
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Background {
+        Background::sky()
+    }
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Background {
+        Background::Solid(color)
+    }
+}
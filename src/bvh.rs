@@ -0,0 +1,253 @@
+use rand::Rng;
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+
+// Bounding volume hierarchy built with the surface area heuristic (SAH).
+//
+// Nodes are stored in one flat vector with the root at index 0, and the items are
+// reordered during the build so that every leaf refers to a contiguous range of them.
+// Unbounded items (e.g. planes) can't be placed in the tree; they are kept after the
+// others and tested for every ray.
+pub struct Bvh<T> {
+    items: Vec<T>,
+    nodes: Vec<Node>,
+    // Number of items in the tree, the rest are unbounded
+    bounded: usize,
+    bbox: Aabb,
+}
+
+struct Node {
+    bbox: Aabb,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Leaf { first: usize, count: usize },
+    Interior { left: usize, right: usize },
+}
+
+const SAH_BINS: usize = 16;
+const MAX_LEAF_SIZE: usize = 4;
+// Relative cost of visiting a node compared to testing one item.
+const TRAVERSAL_COST: f32 = 0.5;
+
+#[derive(Clone, Copy)]
+struct BuildItem {
+    bbox: Aabb,
+    centroid: glam::Vec3,
+    index: usize,
+}
+
+impl<T> Bvh<T> {
+    // Builds a hierarchy over items, using bounds to get the bounding box of each of them.
+    pub fn build(items: Vec<T>, bounds: impl Fn(&T) -> Aabb) -> Bvh<T> {
+        let mut build_items = items.iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let bbox = bounds(item);
+                BuildItem { bbox, centroid: bbox.centroid(), index }
+            })
+            .collect::<Vec<BuildItem>>();
+        let bounded = partition(&mut build_items, |bi| bi.bbox.is_bounded());
+        let bbox = build_items.iter().fold(Aabb::EMPTY, |acc, bi| Aabb::enclosing(acc, bi.bbox));
+
+        let mut nodes = Vec::with_capacity(2 * bounded.max(1));
+        if bounded == 0 {
+            nodes.push(Node { bbox: Aabb::EMPTY, kind: NodeKind::Leaf { first: 0, count: 0 } });
+        } else {
+            Self::build_node(&mut build_items[..bounded], 0, &mut nodes);
+        }
+
+        // Reorder the items to match the leaf ranges.
+        let mut slots = items.into_iter().map(Some).collect::<Vec<Option<T>>>();
+        let items = build_items.iter()
+            .map(|bi| slots[bi.index].take().expect("Every item is referenced exactly once."))
+            .collect();
+
+        Bvh { items, nodes, bounded, bbox }
+    }
+
+    fn build_node(build_items: &mut [BuildItem], first: usize, nodes: &mut Vec<Node>) -> usize {
+        let bbox = build_items.iter().fold(Aabb::EMPTY, |acc, bi| Aabb::enclosing(acc, bi.bbox));
+        let node_index = nodes.len();
+        nodes.push(Node { bbox, kind: NodeKind::Leaf { first, count: build_items.len() } });
+
+        let count = build_items.len();
+        if count <= 1 {
+            return node_index;
+        }
+
+        let Some(split) = Self::find_split(build_items, bbox, count) else {
+            return node_index;
+        };
+
+        let left = Self::build_node(&mut build_items[..split], first, nodes);
+        let right = Self::build_node(&mut build_items[split..], first + split, nodes);
+        nodes[node_index].kind = NodeKind::Interior { left, right };
+        node_index
+    }
+
+    // Partitions the items along the best SAH split and returns the split position,
+    // or None when keeping them all in a leaf is cheaper.
+    fn find_split(build_items: &mut [BuildItem], bbox: Aabb, count: usize) -> Option<usize> {
+        let centroid_bounds = build_items.iter()
+            .fold(Aabb::EMPTY, |acc, bi| Aabb::enclosing(acc, Aabb::from_points(bi.centroid, bi.centroid)));
+
+        let mut best: Option<(f32, usize, usize)> = None; // cost, axis, bin
+        for axis in 0..3 {
+            let extent = centroid_bounds.axis(axis);
+            if extent.size() <= 0.0 {
+                continue;
+            }
+
+            let mut bin_boxes = [Aabb::EMPTY; SAH_BINS];
+            let mut bin_counts = [0usize; SAH_BINS];
+            for bi in build_items.iter() {
+                let b = Self::bin_index(bi.centroid[axis], extent);
+                bin_boxes[b] = Aabb::enclosing(bin_boxes[b], bi.bbox);
+                bin_counts[b] += 1;
+            }
+
+            // Sweep from the right to get the area and count of everything above each split plane.
+            let mut right_area = [0.0; SAH_BINS];
+            let mut right_count = [0usize; SAH_BINS];
+            let mut acc_box = Aabb::EMPTY;
+            let mut acc_count = 0;
+            for b in (1..SAH_BINS).rev() {
+                acc_box = Aabb::enclosing(acc_box, bin_boxes[b]);
+                acc_count += bin_counts[b];
+                right_area[b] = acc_box.surface_area();
+                right_count[b] = acc_count;
+            }
+
+            let mut acc_box = Aabb::EMPTY;
+            let mut acc_count = 0;
+            for b in 1..SAH_BINS {
+                acc_box = Aabb::enclosing(acc_box, bin_boxes[b - 1]);
+                acc_count += bin_counts[b - 1];
+                if acc_count == 0 || right_count[b] == 0 {
+                    continue;
+                }
+                let cost = acc_count as f32 * acc_box.surface_area() + right_count[b] as f32 * right_area[b];
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, b));
+                }
+            }
+        }
+
+        let parent_area = bbox.surface_area();
+        let split = match best {
+            Some((cost, axis, bin)) => {
+                let split_cost = if parent_area > 0.0 {
+                    TRAVERSAL_COST + cost / parent_area
+                } else {
+                    TRAVERSAL_COST
+                };
+                if count <= MAX_LEAF_SIZE && split_cost >= count as f32 {
+                    return None;
+                }
+                let extent = centroid_bounds.axis(axis);
+                partition(build_items, |bi| Self::bin_index(bi.centroid[axis], extent) < bin)
+            }
+            // All centroids coincide, so no plane separates them.
+            None if count <= MAX_LEAF_SIZE => return None,
+            None => count / 2,
+        };
+        Some(split)
+    }
+
+    fn bin_index(x: f32, extent: Interval) -> usize {
+        let b = ((x - extent.min) / extent.size() * SAH_BINS as f32) as usize;
+        b.min(SAH_BINS - 1)
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    // Finds the closest item hit within the interval. The hit function is called for
+    // every candidate item and reports a hit record along with any extra data R.
+    pub fn traverse<'a, R>(
+        &'a self,
+        ray: &Ray,
+        interval: Interval,
+        mut hit: impl FnMut(&'a T, &Ray, Interval) -> Option<(HitRecord, R)>,
+    ) -> Option<(HitRecord, R)> {
+        let mut closest: Option<(HitRecord, R)> = None;
+        let mut interval = interval;
+
+        for item in &self.items[self.bounded..] {
+            if let Some((hit_record, data)) = hit(item, ray, interval) {
+                interval.max = hit_record.t;
+                closest = Some((hit_record, data));
+            }
+        }
+
+        let mut stack = Vec::with_capacity(64);
+        if self.nodes[0].bbox.hit(ray, interval).is_some() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            match node.kind {
+                NodeKind::Leaf { first, count } => {
+                    for item in &self.items[first..first + count] {
+                        if let Some((hit_record, data)) = hit(item, ray, interval) {
+                            interval.max = hit_record.t;
+                            closest = Some((hit_record, data));
+                        }
+                    }
+                }
+                NodeKind::Interior { left, right } => {
+                    let t_left = self.nodes[left].bbox.hit(ray, interval);
+                    let t_right = self.nodes[right].bbox.hit(ray, interval);
+                    // Visit the nearer child first so that its hits can cull the farther one.
+                    match (t_left, t_right) {
+                        (Some(tl), Some(tr)) if tl <= tr => { stack.push(right); stack.push(left); }
+                        (Some(_), Some(_)) => { stack.push(left); stack.push(right); }
+                        (Some(_), None) => stack.push(left),
+                        (None, Some(_)) => stack.push(right),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+impl<T: Hittable> Bvh<T> {
+    pub fn new(items: Vec<T>) -> Bvh<T> {
+        Self::build(items, |item| item.bounding_box())
+    }
+}
+
+impl<T: Hittable> Hittable for Bvh<T> {
+    fn hit<R: Rng>(&self, ray: &Ray, interval: Interval, rng: &mut R) -> Option<(HitRecord, &Material)> {
+        self.traverse(ray, interval, |item, ray, interval| item.hit(ray, interval, rng))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+// In-place partition; returns the number of elements for which pred holds, which are moved to the front.
+fn partition<T>(items: &mut [T], pred: impl Fn(&T) -> bool) -> usize {
+    let mut split = 0;
+    for i in 0..items.len() {
+        if pred(&items[i]) {
+            items.swap(split, i);
+            split += 1;
+        }
+    }
+    split
+}
@@ -1,15 +1,16 @@
 use crate::interval::Interval;
 use crate::ray::{Ray, Point};
 use crate::hittable::{Hittable, Shape};
+use crate::background::Background;
+use crate::image::Image;
+use crate::pdf::Pdf;
+use crate::spectrum;
+use crate::render::RenderOptions;
 use glam::{vec3, Vec3};
-use indicatif::ParallelProgressIterator;
-use std::f32::INFINITY;
-use std::io::Write;
-use itertools::{self, Itertools};
 use rand::prelude::*;
-use rayon::prelude::*;
+use rand::rngs::SmallRng;
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct CameraBuilder {
     vfov: f32,  // Vertical view angle (field of view)
     samples_per_pixel: u32,
@@ -24,10 +25,20 @@ pub struct CameraBuilder {
 
     defocus_angle: f32,
     focus_dist: f32,
+
+    background: Background,
+    seed: u64,
+
+    // Exposure interval the ray times are spread over
+    shutter_open: f32,
+    shutter_close: f32,
+
+    lights: Vec<Shape>,
+    spectral: bool,
 }
 
-impl CameraBuilder {
-    pub fn default() -> CameraBuilder {
+impl Default for CameraBuilder {
+    fn default() -> CameraBuilder {
         // camera
         let vfov = 90.0;  // Vertical view angle (field of view)
         let look_from = Point::new(0.0, 0.0, 0.0);  // Point camera is looking from
@@ -43,9 +54,23 @@ impl CameraBuilder {
         let defocus_angle = 0.0;
         let focus_dist = 1.0;
     
-        CameraBuilder { vfov, samples_per_pixel, max_depth, look_from, look_at, vup, image_width, aspect_ratio, defocus_angle, focus_dist }
+        let background = Background::sky();
+        let seed = 0;
+
+        // An instant, so nothing is blurred
+        let shutter_open = 0.0;
+        let shutter_close = 0.0;
+
+        // Nothing to sample directly
+        let lights = Vec::new();
+
+        let spectral = false;
+
+        CameraBuilder { vfov, samples_per_pixel, max_depth, look_from, look_at, vup, image_width, aspect_ratio, defocus_angle, focus_dist, background, seed, shutter_open, shutter_close, lights, spectral }
     }
+}
 
+impl CameraBuilder {
     pub fn set_aspect_ratio(&mut self, ratio: f32) -> CameraBuilder {
         self.aspect_ratio = ratio;
         self.clone()
@@ -83,8 +108,44 @@ impl CameraBuilder {
         self.clone()
     }
 
+    pub fn set_background<B: Into<Background>>(&mut self, background: B) -> CameraBuilder {
+        self.background = background.into();
+        self.clone()
+    }
+
+    // Renders with the same seed (and settings) are identical.
+    pub fn set_seed(&mut self, seed: u64) -> CameraBuilder {
+        self.seed = seed;
+        self.clone()
+    }
+
+    // Rays get times spread over [open, close], moving objects blur over that interval.
+    pub fn set_shutter(&mut self, open: f32, close: f32) -> CameraBuilder {
+        self.shutter_open = open;
+        self.shutter_close = close;
+        self.clone()
+    }
+
+    // Shapes (usually the emitters in the world) that diffuse bounces send part of their
+    // rays towards. This only changes the noise, not what the image converges to.
+    pub fn set_lights(&mut self, lights: Vec<Shape>) -> CameraBuilder {
+        self.lights = lights;
+        self.clone()
+    }
+
+    // Traces every path at one random wavelength, so that dispersive materials split light
+    // into its colors. Needs more samples than RGB rendering for the same noise.
+    pub fn set_spectral(&mut self, spectral: bool) -> CameraBuilder {
+        self.spectral = spectral;
+        self.clone()
+    }
+
+    pub(crate) fn vup(&self) -> Vec3 {
+        self.vup
+    }
+
     pub fn build(&self) -> Camera {
-        Camera::new(
+        let mut camera = Camera::new(
             self.image_width,
             self.aspect_ratio,
             self.vfov,
@@ -94,38 +155,39 @@ impl CameraBuilder {
             self.look_at,
             self.vup,
             self.defocus_angle,
-            self.focus_dist)
+            self.focus_dist);
+        camera.background = self.background.clone();
+        camera.seed = self.seed;
+        camera.shutter = Interval::new(self.shutter_open, self.shutter_close);
+        camera.lights = self.lights.clone();
+        camera.spectral = self.spectral;
+        camera
     }
 }
 
 pub struct Camera {
     pub aspect_ratio: f32,
     pub image_width: u32,
-    image_height: u32,
+    pub(crate) image_height: u32,
     center: Point,
     pixel00_loc: Point,
     pixel_delta_u: Vec3,
     pixel_delta_v: Vec3,
-    samples_per_pixel: u32,
+    pub(crate) samples_per_pixel: u32,
     max_depth: u32,
     pub defocus_disk_u: Vec3,
     pub defocus_disk_v: Vec3,
     pub defocus_angle: f32,
+    background: Background,
+    seed: u64,
+    shutter: Interval,
+    lights: Vec<Shape>,
+    spectral: bool,
 }
 pub type Color = Vec3;
-fn stringify_color(color: &Color) -> String {
-    format!(
-        "{} {} {}",
-        color.x * 255.99,
-        color.y * 255.99,
-        color.z * 255.99
-    )
-}
-fn linnear_to_gamma(color: &Color) -> Color {
-    Color { x: color.x.sqrt(), y: color.y.sqrt(), z: color.z.sqrt() }
-}
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         image_width: u32,
         aspect_ratio: f32,
@@ -185,97 +247,132 @@ impl Camera {
             defocus_disk_u,
             defocus_disk_v,
             defocus_angle,
+            background: Background::sky(),
+            seed: 0,
+            shutter: Interval::new(0.0, 0.0),
+            lights: Vec::new(),
+            spectral: false,
         }
     }
 
-    pub fn render(&self, world: Vec<Shape>) -> () {
-        let pixel_colors = (0..self.image_height)
-            .cartesian_product(0..self.image_width)
-            .collect::<Vec<(u32, u32)>>()
-            .into_par_iter()
-            .progress_count(self.image_height as u64 * self.image_width as u64)
-            .map(|(j, i)| {
-                let pixel_sum = (0..self.samples_per_pixel)
-                .map(|_| self.get_ray(i, j))
-                .map(|ray| self.ray_color(&ray, self.max_depth, &world))
-                .sum::<Color>();
-            pixel_sum / self.samples_per_pixel as f32
-            })
-            .collect::<Vec<Color>>();
-        
-        // create the file
-        let pixel_strings = pixel_colors.iter()
-            .map(|pc| linnear_to_gamma(pc))
-            .map(|pc| stringify_color(&pc))
-            .join("\n");
-        let string_header = format!("P3\n{} {}\n255\n", self.image_width, self.image_height);
-        let file_content = string_header + &pixel_strings;
-        
-        std::fs::File::create("image.ppm")
-        .expect("Should be able to create a new file.")
-        .write_all(file_content.as_bytes())
-        .expect("Should be able to write to it as well.");
+    pub fn render<W: Hittable + Sync>(&self, world: &W) -> Image {
+        self.render_with(world, &RenderOptions::default()).expect("Rendering without a checkpoint or cancel token cannot fail.")
+    }
+
+    // Hash of everything that decides the rendered pixels, apart from the world. The lights
+    // are part of the camera, as they steer where paths are sampled.
+    pub(crate) fn fingerprint(&self) -> u64 {
+        let floats = [self.center, self.pixel00_loc, self.pixel_delta_u, self.pixel_delta_v, self.defocus_disk_u, self.defocus_disk_v]
+            .into_iter()
+            .flat_map(|v| v.to_array())
+            .chain([self.defocus_angle, self.shutter.min, self.shutter.max])
+            .chain(background_values(&self.background))
+            .chain(self.lights.iter().flat_map(light_values))
+            .map(|f| f.to_bits() as u64);
+        let background_kind = match self.background {
+            Background::Solid(_) => 0,
+            Background::Gradient { .. } => 1,
+            Background::EnvironmentMap { .. } => 2,
+        };
+        [self.image_width as u64, self.image_height as u64, self.samples_per_pixel as u64, self.max_depth as u64, self.seed, self.spectral as u64]
+            .into_iter()
+            .chain([background_kind, self.lights.len() as u64])
+            .chain(floats)
+            .fold(0, |hash, value| splitmix64(hash ^ value))
+    }
+
+    // Random number generator for pixel i,j. It only depends on the seed, the pixel and the
+    // pass (for renders that revisit pixels), so the result does not depend on how pixels
+    // are spread over threads.
+    pub(crate) fn pixel_rng(&self, i: u32, j: u32, pass: u32) -> SmallRng {
+        let pixel = (j as u64) << 32 | i as u64;
+        let pass_key = (pass as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        SmallRng::seed_from_u64(splitmix64(self.seed ^ splitmix64(pixel) ^ pass_key))
+    }
+
+    // Radiance along one randomly sampled camera ray through pixel i,j.
+    pub(crate) fn sample_pixel<W: Hittable, R: Rng>(&self, i: u32, j: u32, world: &W, rng: &mut R) -> Color {
+        let mut ray = self.get_ray(i, j, rng);
+        if self.spectral {
+            let wavelength = spectrum::sample_wavelength(rng);
+            ray.wavelength = Some(wavelength);
+            return spectrum::rgb_at_wavelength(self.ray_color(&ray, self.max_depth, world, rng), wavelength);
+        }
+        self.ray_color(&ray, self.max_depth, world, rng)
     }
 
-    fn ray_color(&self, ray: &Ray, depth: u32, world: &Vec<Shape>) -> Color {
-        if depth <= 0 {
+    fn ray_color<W: Hittable, R: Rng>(&self, ray: &Ray, depth: u32, world: &W, rng: &mut R) -> Color {
+        if depth == 0 {
             return Color::ZERO;
         }
-        let ray_trace = world.iter().fold(None, |acc, elem| 
-            match acc {
-                None => elem.hit(&ray, Interval::new(0.001, INFINITY)),
-                Some((hr, material)) => elem.hit(&ray, Interval::new(0.0, hr.t)).or(Some((hr, material))),
-            });
+        let ray_trace = world.hit(ray, Interval::new(0.001, f32::INFINITY), rng);
 
         if let Some((hit_record, material)) = ray_trace {
-            if let Some((scattered_ray, attenuation)) = material.scatter(ray, &hit_record) {
-                return attenuation * self.ray_color(&scattered_ray, depth - 1, world);
+            let emitted = material.emitted(&hit_record);
+
+            // Diffuse materials draw their next direction half of the time from their own
+            // density and half of the time towards the lights, weighting by the combined density.
+            if let Some(material_pdf) = material.scattering_pdf(ray, &hit_record) {
+                let light_pdf = Pdf::Shapes { shapes: &self.lights, origin: hit_record.point };
+                let mixture = Pdf::Mixture(&light_pdf, &material_pdf);
+                let pdf = if self.lights.is_empty() { &material_pdf } else { &mixture };
+
+                let direction = pdf.generate(rng);
+                let pdf_value = pdf.value(direction);
+                if pdf_value <= 0.0 {
+                    return emitted;
+                }
+                let scattered_ray = ray.scattered(hit_record.point, direction);
+                let weight = material.eval(ray, &hit_record, direction) / pdf_value;
+                return emitted + weight * self.ray_color(&scattered_ray, depth - 1, world, rng);
+            }
+
+            if let Some((scattered_ray, attenuation)) = material.scatter(ray, &hit_record, rng) {
+                return emitted + attenuation * self.ray_color(&scattered_ray, depth - 1, world, rng);
             } else {
-                // Not getting a scatter back is absorbtion
-                return Color::new(0.0, 0.0, 0.0);
+                // Not getting a scatter back is absorbtion (or a light source)
+                return emitted;
             }
         }
 
-        // background
-        let unit_direction = ray.dir.normalize();
-        let a = 0.5 * (unit_direction.y + 1.0);
-        vec3(1.0, 1.0, 1.0).lerp(vec3(0.5, 0.7, 1.0), a)
+        self.background.color(ray)
     }
 
-    fn get_ray(&self, i: u32, j: u32) -> Ray {
+    fn get_ray<R: Rng>(&self, i: u32, j: u32, rng: &mut R) -> Ray {
         // Get a randomly-sampled camera ray for the pixel at location i,j, originating from
         // the camera defocus disk.
         let pixel_center =
                     self.pixel00_loc + (i as f32 * self.pixel_delta_u) + (j as f32 * self.pixel_delta_v);
-        let pixel_sample = pixel_center + self.pixel_sample_square();
+        let pixel_sample = pixel_center + self.pixel_sample_square(rng);
 
-        let ray_origin = if self.defocus_angle <= 0.0 { self.center } else { self.defocus_disk_sample() };
+        let ray_origin = if self.defocus_angle <= 0.0 { self.center } else { self.defocus_disk_sample(rng) };
         let ray_direction = pixel_sample - ray_origin;
-        Ray {
-            orig: ray_origin,
-            dir: ray_direction,
-        }
+        // Only draw a time for an open shutter, so still renders keep their random sequence.
+        let ray_time = if self.shutter.size() > 0.0 {
+            self.shutter.min + rng.gen::<f32>() * self.shutter.size()
+        } else {
+            self.shutter.min
+        };
+        Ray::with_time(ray_origin, ray_direction, ray_time)
     }
 
-    fn pixel_sample_square(&self) -> Vec3 {
-        let mut rng = rand::thread_rng();
+    fn pixel_sample_square<R: Rng>(&self, rng: &mut R) -> Vec3 {
         let px = -0.5 + rng.gen::<f32>();
         let py = -0.5 + rng.gen::<f32>();
         px * self.pixel_delta_u + py * self.pixel_delta_v
     }
 
-    fn defocus_disk_sample(&self) -> Vec3 {
+    fn defocus_disk_sample<R: Rng>(&self, rng: &mut R) -> Vec3 {
         // Returns a random point in the camera defocus disk.
-        let p = Self::random_in_unit_disk();
-        return self.center + (p[0] * self.defocus_disk_u) + (p[1] * self.defocus_disk_v);
+        let p = Self::random_in_unit_disk(rng);
+        self.center + (p[0] * self.defocus_disk_u) + (p[1] * self.defocus_disk_v)
     }
 
     fn degrees_to_radians(degrees: f32) -> f32 {
         degrees * std::f32::consts::PI / 180.0
     }
 
-    fn random_in_unit_disk() -> Vec3 {
-        let mut rng = rand::thread_rng();
+    fn random_in_unit_disk<R: Rng>(rng: &mut R) -> Vec3 {
         loop {
             let p = vec3(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0), 0.0);
             if p.length_squared() < 1.0 {
@@ -283,7 +380,38 @@ impl Camera {
             }
         }
     }
+}
+
+// Numbers describing a background for the fingerprint, including every pixel of a map.
+fn background_values(background: &Background) -> Vec<f32> {
+    match background {
+        Background::Solid(color) => color.to_array().to_vec(),
+        Background::Gradient { bottom, top } => [bottom.to_array(), top.to_array()].concat(),
+        Background::EnvironmentMap { image, rotation, intensity } => {
+            [*rotation, *intensity, image.width() as f32, image.height() as f32]
+                .into_iter()
+                .chain(image.pixels().iter().flat_map(|pixel| pixel.to_array()))
+                .collect()
+        }
+    }
+}
 
+// Numbers describing where a light is, for the fingerprint. Lights are spheres, quads or
+// triangles; anything else is described by its bounds.
+fn light_values(light: &Shape) -> Vec<f32> {
+    let points = match light {
+        Shape::Sphere { center, radius, .. } => vec![*center, Vec3::splat(*radius)],
+        Shape::Quad { q, u, v, .. } => vec![*q, *u, *v],
+        Shape::Triangle { triangle, .. } => triangle.vertices.to_vec(),
+        other => vec![other.bounding_box().min(), other.bounding_box().max()],
+    };
+    points.iter().flat_map(|point| point.to_array()).collect()
+}
 
-    
+// SplitMix64 finalizer, used to turn structured seeds (like pixel coordinates) into well mixed ones.
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
@@ -0,0 +1,137 @@
+// Constructive solid geometry: two solids combined by union, intersection or difference.
+//
+// Along a ray, the surfaces of both operands are crossed one after another. Walking over
+// those crossings in order, and keeping track of whether the ray is inside each operand,
+// shows where it enters or leaves the combined solid. This needs closed operands (spheres,
+// boxes, closed meshes or other combinations); a plane counts as the half space behind it.
+
+use glam::Vec3;
+use rand::Rng;
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::hittable::{HitRecord, Hittable, Shape};
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::Ray;
+
+// Distance from one crossing to where the search for the next one starts.
+const STEP: f32 = 0.0001;
+// Guards against endless searches on broken geometry.
+const MAX_CROSSINGS: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsgOperation {
+    // Inside either solid
+    Union,
+    // Inside both solids
+    Intersection,
+    // Inside the first solid but not the second
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_a || in_b,
+            CsgOperation::Intersection => in_a && in_b,
+            CsgOperation::Difference => in_a && !in_b,
+        }
+    }
+
+    pub(crate) fn bounding_box(self, a: Aabb, b: Aabb) -> Aabb {
+        match self {
+            CsgOperation::Union => Aabb::enclosing(a, b),
+            CsgOperation::Intersection => Aabb::overlap(a, b),
+            CsgOperation::Difference => a,
+        }
+    }
+}
+
+// Nearest point within the interval where the ray crosses the surface of the combined solid.
+// The hit record and material are those of the operand surface found there, with the sides
+// of the second solid's surface swapped for differences, where it bounds the result from
+// the outside in.
+pub(crate) fn hit<'a, R: Rng>(
+    operation: CsgOperation,
+    a: &'a Bvh<Shape>,
+    b: &'a Bvh<Shape>,
+    ray: &Ray,
+    interval: Interval,
+    rng: &mut R,
+) -> Option<(HitRecord, &'a Material)> {
+    let mut a_crossings = Crossings::new(a, ray, rng);
+    let mut b_crossings = Crossings::new(b, ray, rng);
+    let mut in_a = a_crossings.starts_inside(ray, rng);
+    let mut in_b = b_crossings.starts_inside(ray, rng);
+    loop {
+        let from_a = match (&a_crossings.next, &b_crossings.next) {
+            (None, None) => return None,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (Some((hit_a, _)), Some((hit_b, _))) => hit_a.t <= hit_b.t,
+        };
+        let (hit_record, material) = if from_a { a_crossings.advance(ray, rng) } else { b_crossings.advance(ray, rng) }?;
+        if hit_record.t >= interval.max {
+            return None;
+        }
+
+        let was_inside = operation.contains(in_a, in_b);
+        // Front faces are where the ray enters an operand. Setting the state rather than
+        // toggling it recovers from crossings missed at edges, where two faces meet.
+        if from_a {
+            in_a = hit_record.front_face;
+        } else {
+            in_b = hit_record.front_face;
+        }
+        if operation.contains(in_a, in_b) != was_inside && interval.surrounds(hit_record.t) {
+            let front_face = if operation == CsgOperation::Difference && !from_a {
+                !hit_record.front_face
+            } else {
+                hit_record.front_face
+            };
+            return Some((HitRecord { front_face, ..hit_record }, material));
+        }
+    }
+}
+
+// Every crossing of the ray with the surface of the shapes in order, including those behind
+// the ray origin. Both operands share the random number generator, so their crossings are
+// stepped through by hand rather than by two iterators.
+struct Crossings<'a> {
+    shapes: &'a Bvh<Shape>,
+    next: Option<(HitRecord, &'a Material)>,
+    remaining: usize,
+}
+
+impl<'a> Crossings<'a> {
+    fn new<R: Rng>(shapes: &'a Bvh<Shape>, ray: &Ray, rng: &mut R) -> Crossings<'a> {
+        Crossings { shapes, next: shapes.hit(ray, Interval::UNIVERSE, rng), remaining: MAX_CROSSINGS - 1 }
+    }
+
+    // Whether the ray is inside the shapes before their first crossing, where it is on the
+    // back side of that surface. A ray that never crosses them is outside a bounded solid,
+    // but may run inside an unbounded one, e.g. parallel to a plane, which a crossing along
+    // one of the axes from the ray origin tells.
+    fn starts_inside<R: Rng>(&self, ray: &Ray, rng: &mut R) -> bool {
+        if let Some((hit_record, _)) = &self.next {
+            return !hit_record.front_face;
+        }
+        if self.shapes.bounding_box().is_bounded() {
+            return false;
+        }
+        [Vec3::X, Vec3::Y, Vec3::Z, -Vec3::X, -Vec3::Y, -Vec3::Z]
+            .into_iter()
+            .find_map(|dir| self.shapes.hit(&ray.scattered(ray.orig, dir), Interval::new(0.0, f32::INFINITY), rng))
+            .is_some_and(|(hit_record, _)| !hit_record.front_face)
+    }
+
+    // Returns the next crossing and looks for the one after it.
+    fn advance<R: Rng>(&mut self, ray: &Ray, rng: &mut R) -> Option<(HitRecord, &'a Material)> {
+        let current = self.next.take()?;
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.next = self.shapes.hit(ray, Interval::new(current.0.t + STEP, f32::INFINITY), rng);
+        }
+        Some(current)
+    }
+}
@@ -1,64 +1,620 @@
-use glam::Vec3;
+use std::f32::consts::PI;
+use glam::{Affine3A, Vec3};
+use rand::Rng;
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::csg::{self, CsgOperation};
 use crate::ray::{Point, Ray};
 use crate::interval::Interval;
 use crate::material::Material;
+use crate::mesh::{Mesh, Triangle};
+use crate::pdf::{random_on_unit_sphere, Onb};
+use crate::roots::{solve_quadratic, solve_quartic};
+use crate::texture::Texture;
+use std::sync::Arc;
 
 pub struct HitRecord {
     pub point: Point,
     pub normal: Vec3,
     pub t: f32,
     pub front_face: bool,
+    // Surface coordinates of the hit point
+    pub u: f32,
+    pub v: f32,
 }
 
 impl HitRecord {
-    fn new_from_ray(out_normal: Vec3, t: f32, ray: &Ray) -> HitRecord {
+    pub(crate) fn new_from_ray(out_normal: Vec3, t: f32, (u, v): (f32, f32), ray: &Ray) -> HitRecord {
         let front_face =  ray.dir.dot(out_normal) < 0.0;
         let normal = if front_face { out_normal } else { -out_normal };
-        HitRecord { point: ray.at(t), normal: normal, t: t, front_face: front_face }
+        HitRecord { point: ray.at(t), normal, t, front_face, u, v }
     }
 }
 
 pub trait Hittable {
-    fn hit(&self, ray: &Ray, interval: Interval) -> Option<(HitRecord, &Material)>;
+    // Closest hit within the interval. The random number generator is for shapes that are
+    // hit at random, like participating media.
+    fn hit<R: Rng>(&self, ray: &Ray, interval: Interval, rng: &mut R) -> Option<(HitRecord, &Material)>;
+    fn bounding_box(&self) -> Aabb;
 }
 
+#[derive(Clone)]
 pub enum Shape {
     Sphere {
         center: Point,
         radius: f32,
         material: Material
-    }
+    },
+    // Sphere whose center moves linearly from center0 at time 0 to center1 at time 1.
+    // It rests at the end points outside that interval.
+    MovingSphere {
+        center0: Point,
+        center1: Point,
+        radius: f32,
+        material: Material
+    },
+    // Parallelogram spanned by the edges u and v from the corner q.
+    // normal, d and w are derived from those and cached for the hit test.
+    Quad {
+        q: Point,
+        u: Vec3,
+        v: Vec3,
+        normal: Vec3,
+        d: f32,
+        w: Vec3,
+        material: Material
+    },
+    Triangle {
+        triangle: Triangle,
+        material: Material
+    },
+    // Infinite plane through point. The basis has w along the normal and u, v in the plane.
+    // Planes have no bounds, so a Bvh tests them for every ray.
+    Plane {
+        point: Point,
+        onb: Onb,
+        material: Material
+    },
+    // Round disk facing along onb.w.
+    Disk {
+        center: Point,
+        onb: Onb,
+        radius: f32,
+        material: Material
+    },
+    // Cylinder with caps, around the axis from base to base + height * onb.w.
+    Cylinder {
+        base: Point,
+        onb: Onb,
+        height: f32,
+        radius: f32,
+        material: Material
+    },
+    // Cone with a capped base of the given radius and its apex at base + height * onb.w.
+    Cone {
+        base: Point,
+        onb: Onb,
+        height: f32,
+        radius: f32,
+        material: Material
+    },
+    // Ring around the axis onb.w: a tube of minor_radius whose center line is a circle of
+    // major_radius.
+    Torus {
+        center: Point,
+        onb: Onb,
+        major_radius: f32,
+        minor_radius: f32,
+        material: Material
+    },
+    // Shared so that one loaded model can appear in the scene several times.
+    Mesh(Arc<Mesh>),
+    // Smoke or fog of constant density filling a closed boundary. Rays scatter off the
+    // medium after a random, exponentially distributed distance inside it.
+    ConstantMedium {
+        boundary: Arc<Vec<Shape>>,
+        neg_inv_density: f32,
+        phase_function: Material
+    },
+    // Shapes placed by an affine transform from their own (object) space into the world.
+    // The object is shared, so it can be placed many times without copying its geometry.
+    // The inverse transform and the world space bounds are cached.
+    Instance {
+        object: Arc<Bvh<Shape>>,
+        transform: Affine3A,
+        inverse: Affine3A,
+        bbox: Aabb,
+    },
+    // Union, intersection or difference of two solids, each made of one or more shapes.
+    // The operands are shared like the objects of instances.
+    Csg {
+        operation: CsgOperation,
+        a: Arc<Bvh<Shape>>,
+        b: Arc<Bvh<Shape>>,
+        bbox: Aabb,
+    },
 }
 
 impl Hittable for Shape {
-    fn hit(&self, ray: &Ray, interval: Interval) -> Option<(HitRecord, &Material)> {
+    fn hit<R: Rng>(&self, ray: &Ray, interval: Interval, rng: &mut R) -> Option<(HitRecord, &Material)> {
         match self {
-            Self::Sphere { center, radius, material } => {
-                let oc = ray.orig - *center;
-                let a = ray.dir.length_squared();
-                let half_b = oc.dot(ray.dir);
-                let c = oc.length_squared() - radius*radius;
-                let discriminant = half_b*half_b - a*c;
-                
-                if discriminant < 0.0 {
-                    None
-                } else {
-                    let sqrtd = discriminant.sqrt();
-                    let t =
-                        interval.surround_where((-half_b - sqrtd) / a)
-                        .or((interval).surround_where((-half_b + sqrtd) / a))?;
-        
-                    let hit_point = ray.at(t);
-                    let outward_normal = (hit_point - *center) / *radius;
-                    Some((HitRecord::new_from_ray(outward_normal, t, &ray), material))
+            Self::Sphere { center, radius, material } => hit_sphere(*center, *radius, ray, interval).map(|hr| (hr, material)),
+            Self::MovingSphere { center0, center1, radius, material } => {
+                let center = center0.lerp(*center1, ray.time.clamp(0.0, 1.0));
+                hit_sphere(center, *radius, ray, interval).map(|hr| (hr, material))
+            }
+            Self::Quad { q, u, v, normal, d, w, material } =>
+                hit_quad(*q, *u, *v, *normal, *d, *w, ray, interval).map(|hr| (hr, material)),
+            Self::Triangle { triangle, material } => triangle.hit(ray, interval).map(|hr| (hr, material)),
+            Self::Plane { point, onb, material } => {
+                let (orig, dir) = local_ray(*point, onb, ray);
+                let t = closest(plane_crossing(orig, dir, 0.0, -Vec3::Z), interval)?.0;
+                let p = orig + t * dir;
+                let uv = (p.x.rem_euclid(1.0), p.y.rem_euclid(1.0));
+                Some((HitRecord::new_from_ray(onb.w, t, uv, ray), material))
+            }
+            Self::Disk { center, onb, radius, material } => {
+                let (orig, dir) = local_ray(*center, onb, ray);
+                let candidates = plane_crossing(orig, dir, 0.0, Vec3::Z)
+                    .filter(|&(t, _)| (orig + t * dir).truncate().length() <= *radius);
+                let (t, _) = closest(candidates, interval)?;
+                let uv = polar_uv(orig + t * dir, *radius);
+                Some((HitRecord::new_from_ray(onb.w, t, uv, ray), material))
+            }
+            Self::Cylinder { base, onb, height, radius, material } => {
+                let (orig, dir) = local_ray(*base, onb, ray);
+                let along = Interval::new(0.0, *height);
+                // x^2 + y^2 = radius^2
+                let side = solve_quadratic(
+                    dir.truncate().length_squared() as f64,
+                    2.0 * orig.truncate().dot(dir.truncate()) as f64,
+                    (orig.truncate().length_squared() - radius * radius) as f64)
+                    .into_iter()
+                    .map(|t| t as f32)
+                    .filter(|&t| along.contains(orig.z + t * dir.z))
+                    .map(|t| (t, (orig + t * dir).truncate().extend(0.0)));
+                let caps = plane_crossing(orig, dir, 0.0, -Vec3::Z)
+                    .chain(plane_crossing(orig, dir, *height, Vec3::Z))
+                    .filter(|&(t, _)| (orig + t * dir).truncate().length() <= *radius);
+                let (t, normal) = closest(side.chain(caps), interval)?;
+
+                let p = orig + t * dir;
+                let uv = if normal.z == 0.0 { (angle_around_z(p), p.z / height) } else { polar_uv(p, *radius) };
+                Some((HitRecord::new_from_ray(onb.local(normal.normalize()), t, uv, ray), material))
+            }
+            Self::Cone { base, onb, height, radius, material } => {
+                let (orig, dir) = local_ray(*base, onb, ray);
+                let along = Interval::new(0.0, *height);
+                // x^2 + y^2 = (k (height - z))^2, with the slope k of the radius
+                let k2 = (radius / height).powi(2);
+                let w = height - orig.z;
+                let side = solve_quadratic(
+                    (dir.truncate().length_squared() - k2 * dir.z * dir.z) as f64,
+                    2.0 * (orig.truncate().dot(dir.truncate()) + k2 * w * dir.z) as f64,
+                    (orig.truncate().length_squared() - k2 * w * w) as f64)
+                    .into_iter()
+                    .map(|t| t as f32)
+                    .filter(|&t| along.contains(orig.z + t * dir.z))
+                    .map(|t| {
+                        let p = orig + t * dir;
+                        // Gradient of the implicit surface; the apex has none, so point up there.
+                        (t, p.truncate().extend(k2 * (height - p.z)).try_normalize().unwrap_or(Vec3::Z))
+                    });
+                let cap = plane_crossing(orig, dir, 0.0, -Vec3::Z)
+                    .filter(|&(t, _)| (orig + t * dir).truncate().length() <= *radius);
+                let (t, normal) = closest(side.chain(cap), interval)?;
+
+                let p = orig + t * dir;
+                let uv = if normal == -Vec3::Z { polar_uv(p, *radius) } else { (angle_around_z(p), p.z / height) };
+                Some((HitRecord::new_from_ray(onb.local(normal), t, uv, ray), material))
+            }
+            Self::Torus { center, onb, major_radius, minor_radius, material } => {
+                let (orig, dir) = local_ray(*center, onb, ray);
+                let t = hit_torus(orig, dir, *major_radius, *minor_radius, interval)?;
+
+                let p = orig + t * dir;
+                // Nearest point on the center circle of the tube
+                let ring = p.truncate().extend(0.0).try_normalize().unwrap_or(Vec3::X) * *major_radius;
+                let normal = (p - ring).normalize();
+                let tube_angle = (p.z.atan2((p - ring).dot(ring.normalize())) + PI) / (2.0 * PI);
+                let uv = (angle_around_z(p), tube_angle);
+                Some((HitRecord::new_from_ray(onb.local(normal), t, uv, ray), material))
+            }
+            Self::Mesh(mesh) => mesh.hit(ray, interval),
+            Self::ConstantMedium { boundary, neg_inv_density, phase_function } => {
+                // Where the ray enters and leaves the boundary, also behind its origin
+                // (for rays that start inside).
+                let (entry, _) = boundary.hit(ray, Interval::new(f32::NEG_INFINITY, f32::INFINITY), rng)?;
+                let (exit, _) = boundary.hit(ray, Interval::new(entry.t + 0.0001, f32::INFINITY), rng)?;
+
+                let t_enter = entry.t.max(interval.min).max(0.0);
+                let t_exit = exit.t.min(interval.max);
+                if t_enter >= t_exit {
+                    return None;
                 }
+
+                let ray_length = ray.dir.length();
+                let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+                // Exponentially distributed, from a uniform number in (0, 1]
+                let hit_distance = neg_inv_density * (1.0 - rng.gen::<f32>()).ln();
+                if hit_distance > distance_inside_boundary {
+                    return None;
+                }
+
+                let t = t_enter + hit_distance / ray_length;
+                // Normal and side mean nothing inside a volume
+                let hit_record = HitRecord { point: ray.at(t), normal: Vec3::X, t, front_face: true, u: 0.0, v: 0.0 };
+                Some((hit_record, phase_function))
+            }
+            Self::Instance { object, transform, inverse, .. } => {
+                // The direction is not normalized, so t is the same in both spaces.
+                let object_ray = Ray { orig: inverse.transform_point3(ray.orig), dir: inverse.transform_vector3(ray.dir), ..*ray };
+                let (hit_record, material) = object.hit(&object_ray, interval, rng)?;
+                let hit_record = HitRecord {
+                    point: transform.transform_point3(hit_record.point),
+                    // Normals transform with the inverse transpose to stay perpendicular to the surface
+                    normal: inverse.matrix3.transpose().mul_vec3(hit_record.normal).normalize(),
+                    ..hit_record
+                };
+                Some((hit_record, material))
             }
+            Self::Csg { operation, a, b, .. } => csg::hit(*operation, a, b, ray, interval, rng),
         }
     }
+
+    fn bounding_box(&self) -> Aabb {
+        match self {
+            Self::Sphere { center, radius, .. } => sphere_bounding_box(*center, *radius),
+            Self::MovingSphere { center0, center1, radius, .. } =>
+                Aabb::enclosing(sphere_bounding_box(*center0, *radius), sphere_bounding_box(*center1, *radius)),
+            Self::Quad { q, u, v, .. } => {
+                let diagonal1 = Aabb::from_points(*q, *q + *u + *v);
+                let diagonal2 = Aabb::from_points(*q + *u, *q + *v);
+                Aabb::enclosing(diagonal1, diagonal2).pad()
+            }
+            Self::Triangle { triangle, .. } => triangle.bounding_box(),
+            Self::Plane { .. } => Aabb::INFINITE,
+            Self::Disk { center, onb, radius, .. } => disk_bounding_box(*center, onb.w, *radius),
+            Self::Cylinder { base, onb, height, radius, .. } =>
+                Aabb::enclosing(disk_bounding_box(*base, onb.w, *radius), disk_bounding_box(*base + *height * onb.w, onb.w, *radius)),
+            Self::Cone { base, onb, height, radius, .. } => {
+                let apex = *base + *height * onb.w;
+                Aabb::enclosing(disk_bounding_box(*base, onb.w, *radius), Aabb::from_points(apex, apex)).pad()
+            }
+            Self::Torus { center, onb, major_radius, minor_radius, .. } => {
+                // The center circle's bounds, widened by the tube in every direction
+                let extent = disk_extent(onb.w, *major_radius) + Vec3::splat(*minor_radius);
+                Aabb::from_points(*center - extent, *center + extent)
+            }
+            Self::Mesh(mesh) => mesh.bounding_box(),
+            Self::ConstantMedium { boundary, .. } => boundary.bounding_box(),
+            Self::Instance { bbox, .. } | Self::Csg { bbox, .. } => *bbox,
+        }
+    }
+}
+
+// A plain list of shapes, tested one after another.
+impl Hittable for Vec<Shape> {
+    fn hit<R: Rng>(&self, ray: &Ray, interval: Interval, rng: &mut R) -> Option<(HitRecord, &Material)> {
+        self.iter().fold(None, |acc, elem|
+            match acc {
+                None => elem.hit(ray, interval, rng),
+                Some((hr, material)) => elem.hit(ray, Interval::new(interval.min, hr.t), rng).or(Some((hr, material))),
+            })
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.iter().fold(Aabb::EMPTY, |acc, elem| Aabb::enclosing(acc, elem.bounding_box()))
+    }
 }
 
 impl Shape {
     pub fn new_sphere(center: Point, radius: f32, material: &Material) -> Shape {
         Shape::Sphere { center, radius, material: material.clone() }
     }
-}
\ No newline at end of file
+
+    pub fn new_moving_sphere(center0: Point, center1: Point, radius: f32, material: &Material) -> Shape {
+        Shape::MovingSphere { center0, center1, radius, material: material.clone() }
+    }
+
+    pub fn new_quad(q: Point, u: Vec3, v: Vec3, material: &Material) -> Shape {
+        let n = u.cross(v);
+        let normal = n.normalize();
+        let d = normal.dot(q);
+        let w = n / n.dot(n);
+        Shape::Quad { q, u, v, normal, d, w, material: material.clone() }
+    }
+
+    pub fn new_triangle(a: Point, b: Point, c: Point, material: &Material) -> Shape {
+        Shape::Triangle { triangle: Triangle::new(a, b, c), material: material.clone() }
+    }
+
+    pub fn new_plane(point: Point, normal: Vec3, material: &Material) -> Shape {
+        Shape::Plane { point, onb: Onb::new(normal), material: material.clone() }
+    }
+
+    pub fn new_disk(center: Point, normal: Vec3, radius: f32, material: &Material) -> Shape {
+        Shape::Disk { center, onb: Onb::new(normal), radius, material: material.clone() }
+    }
+
+    // Cylinder from the center of its bottom cap to the center of its top cap.
+    pub fn new_cylinder(base: Point, top: Point, radius: f32, material: &Material) -> Shape {
+        let axis = top - base;
+        Shape::Cylinder { base, onb: Onb::new(axis), height: axis.length(), radius, material: material.clone() }
+    }
+
+    // Cone from the center of its base to its apex.
+    pub fn new_cone(base: Point, apex: Point, radius: f32, material: &Material) -> Shape {
+        let axis = apex - base;
+        Shape::Cone { base, onb: Onb::new(axis), height: axis.length(), radius, material: material.clone() }
+    }
+
+    // Torus around center, lying in the plane with the given normal.
+    pub fn new_torus(center: Point, normal: Vec3, major_radius: f32, minor_radius: f32, material: &Material) -> Shape {
+        Shape::Torus { center, onb: Onb::new(normal), major_radius, minor_radius, material: material.clone() }
+    }
+
+    pub fn new_mesh(mesh: Mesh) -> Shape {
+        Shape::Mesh(Arc::new(mesh))
+    }
+
+    // Fills the closed boundary (e.g. a sphere or the sides of a box) with a medium of the
+    // given density and albedo.
+    pub fn new_constant_medium(boundary: Vec<Shape>, density: f32, albedo: Texture) -> Shape {
+        Shape::ConstantMedium { boundary: Arc::new(boundary), neg_inv_density: -1.0 / density, phase_function: Material::Isotropic(albedo) }
+    }
+
+    // Places the shapes in the world with the transform (e.g. Affine3A::from_translation).
+    pub fn new_instance(shapes: Vec<Shape>, transform: Affine3A) -> Shape {
+        Self::instance_of(Arc::new(Bvh::new(shapes)), transform)
+    }
+
+    // The shape moved by a further transform. Instances share their object with the result.
+    pub fn transformed(&self, transform: Affine3A) -> Shape {
+        match self {
+            Self::Instance { object, transform: placement, .. } => Self::instance_of(object.clone(), transform * *placement),
+            shape => Self::new_instance(vec![shape.clone()], transform),
+        }
+    }
+
+    // Combines the solid made of the shapes a with the one made of b, e.g. a lens as the
+    // intersection of two spheres.
+    pub fn new_csg(operation: CsgOperation, a: Vec<Shape>, b: Vec<Shape>) -> Shape {
+        let (a, b) = (Bvh::new(a), Bvh::new(b));
+        let bbox = operation.bounding_box(a.bounding_box(), b.bounding_box());
+        Shape::Csg { operation, a: Arc::new(a), b: Arc::new(b), bbox }
+    }
+
+    fn instance_of(object: Arc<Bvh<Shape>>, transform: Affine3A) -> Shape {
+        let object_box = object.bounding_box();
+        let bbox = if object_box.is_empty() {
+            Aabb::EMPTY
+        } else if !object_box.is_bounded() {
+            Aabb::INFINITE
+        } else {
+            // Bounds of the transformed corners of the object's bounding box
+            let (min, max) = (object_box.min(), object_box.max());
+            (0..8)
+                .map(|i| Vec3::new(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z }))
+                .map(|corner| transform.transform_point3(corner))
+                .fold(Aabb::EMPTY, |bbox, p| Aabb::enclosing(bbox, Aabb::from_points(p, p)))
+                .pad()
+        };
+        Shape::Instance { object, transform, inverse: transform.inverse(), bbox }
+    }
+
+    // Density of direction from origin, when directions are drawn with random_direction.
+    // Spheres, quads and triangles sample the directions that reach them; other shapes
+    // fall back to all directions alike.
+    pub fn pdf_value(&self, origin: Point, direction: Vec3) -> f32 {
+        let towards = Ray::new(origin, direction);
+        let all = Interval::new(0.001, f32::INFINITY);
+        match self {
+            Self::Sphere { center, radius, .. } => {
+                let distance_squared = (*center - origin).length_squared();
+                if distance_squared <= radius * radius {
+                    return 1.0 / (4.0 * PI);
+                }
+                if hit_sphere(*center, *radius, &towards, all).is_none() {
+                    return 0.0;
+                }
+                let cos_theta_max = (1.0 - radius * radius / distance_squared).sqrt();
+                1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+            }
+            Self::Quad { q, u, v, normal, d, w, .. } => {
+                let area = u.cross(*v).length();
+                hit_quad(*q, *u, *v, *normal, *d, *w, &towards, all).map_or(0.0, |hr| area_pdf_value(hr.t, direction, *normal, area))
+            }
+            Self::Triangle { triangle, .. } => {
+                let [a, b, c] = triangle.vertices;
+                let n = (b - a).cross(c - a);
+                let area = 0.5 * n.length();
+                triangle.hit(&towards, all).map_or(0.0, |hr| area_pdf_value(hr.t, direction, n.normalize(), area))
+            }
+            _ => 1.0 / (4.0 * PI),
+        }
+    }
+
+    // Random direction from origin towards the shape, see pdf_value.
+    pub fn random_direction<R: Rng>(&self, origin: Point, rng: &mut R) -> Vec3 {
+        match self {
+            Self::Sphere { center, radius, .. } => {
+                let distance_squared = (*center - origin).length_squared();
+                if distance_squared <= radius * radius {
+                    return random_on_unit_sphere(rng);
+                }
+                // Uniform over the cone of directions that hit the sphere
+                let cos_theta_max = (1.0 - radius * radius / distance_squared).sqrt();
+                let z = 1.0 + rng.gen::<f32>() * (cos_theta_max - 1.0);
+                let phi = 2.0 * PI * rng.gen::<f32>();
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                Onb::new(*center - origin).local(Vec3::new(r * phi.cos(), r * phi.sin(), z))
+            }
+            Self::Quad { q, u, v, .. } => *q + rng.gen::<f32>() * *u + rng.gen::<f32>() * *v - origin,
+            Self::Triangle { triangle, .. } => {
+                // Uniform over the area
+                let [a, b, c] = triangle.vertices;
+                let s = rng.gen::<f32>().sqrt();
+                let r = rng.gen::<f32>();
+                a * (1.0 - s) + b * (s * (1.0 - r)) + c * (s * r) - origin
+            }
+            _ => random_on_unit_sphere(rng),
+        }
+    }
+
+    // The six sides of the axis-aligned box with opposite corners a and b.
+    pub fn new_box(a: Point, b: Point, material: &Material) -> Vec<Shape> {
+        let min = a.min(b);
+        let max = a.max(b);
+
+        let dx = Vec3::new(max.x - min.x, 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y - min.y, 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z - min.z);
+
+        vec![
+            Shape::new_quad(Point::new(min.x, min.y, max.z), dx, dy, material),  // front
+            Shape::new_quad(Point::new(max.x, min.y, max.z), -dz, dy, material), // right
+            Shape::new_quad(Point::new(max.x, min.y, min.z), -dx, dy, material), // back
+            Shape::new_quad(Point::new(min.x, min.y, min.z), dz, dy, material),  // left
+            Shape::new_quad(Point::new(min.x, max.y, max.z), dx, -dz, material), // top
+            Shape::new_quad(Point::new(min.x, min.y, min.z), dx, dz, material),  // bottom
+        ]
+    }
+}
+
+fn hit_sphere(center: Point, radius: f32, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+    let oc = ray.orig - center;
+    let a = ray.dir.length_squared();
+    let half_b = oc.dot(ray.dir);
+    let c = oc.length_squared() - radius*radius;
+    let discriminant = half_b*half_b - a*c;
+
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrtd = discriminant.sqrt();
+    let t =
+        interval.surround_where((-half_b - sqrtd) / a)
+        .or((interval).surround_where((-half_b + sqrtd) / a))?;
+
+    let hit_point = ray.at(t);
+    let outward_normal = (hit_point - center) / radius;
+    let uv = sphere_uv((hit_point - center) / radius.abs());
+    Some(HitRecord::new_from_ray(outward_normal, t, uv, ray))
+}
+
+// Parallelogram spanned by u and v from the corner q. normal, d and w are cached from those.
+#[allow(clippy::too_many_arguments)]
+fn hit_quad(q: Point, u: Vec3, v: Vec3, normal: Vec3, d: f32, w: Vec3, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+    let denom = normal.dot(ray.dir);
+
+    // No hit if the ray is parallel to the plane.
+    if denom.abs() < 1e-8 {
+        return None;
+    }
+
+    let t = interval.surround_where((d - normal.dot(ray.orig)) / denom)?;
+
+    // Express the hit point in the plane's (u, v) basis to see if it is inside the quad.
+    let planar_hitpt_vector = ray.at(t) - q;
+    let alpha = w.dot(planar_hitpt_vector.cross(v));
+    let beta = w.dot(u.cross(planar_hitpt_vector));
+    let unit = Interval::new(0.0, 1.0);
+    if !unit.contains(alpha) || !unit.contains(beta) {
+        return None;
+    }
+
+    Some(HitRecord::new_from_ray(normal, t, (alpha, beta), ray))
+}
+
+fn sphere_bounding_box(center: Point, radius: f32) -> Aabb {
+    // radius may be negative for hollow spheres
+    let r = Vec3::splat(radius.abs());
+    Aabb::from_points(center - r, center + r)
+}
+
+// Ray origin and direction in the frame of onb at origin. The frame is orthonormal, so
+// distances along the ray stay the same.
+fn local_ray(origin: Point, onb: &Onb, ray: &Ray) -> (Point, Vec3) {
+    (onb.to_local(ray.orig - origin), onb.to_local(ray.dir))
+}
+
+// Where a local ray crosses the plane z = height, with the normal to report there.
+fn plane_crossing(orig: Point, dir: Vec3, height: f32, normal: Vec3) -> impl Iterator<Item = (f32, Vec3)> {
+    // No crossing if the ray is parallel to the plane
+    (dir.z.abs() >= 1e-8).then(|| ((height - orig.z) / dir.z, normal)).into_iter()
+}
+
+// The nearest of the candidate (t, normal) pairs inside the interval.
+fn closest(candidates: impl Iterator<Item = (f32, Vec3)>, interval: Interval) -> Option<(f32, Vec3)> {
+    candidates
+        .filter(|&(t, _)| interval.surrounds(t))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+// Nearest t where a local ray hits the torus around the z axis.
+fn hit_torus(orig: Point, dir: Vec3, major_radius: f32, minor_radius: f32, interval: Interval) -> Option<f32> {
+    // The quartic is solved along a unit direction, from the point of the ray closest to the
+    // center, to keep its coefficients small.
+    let length = dir.length() as f64;
+    let d = dir.as_dvec3() / length;
+    let t_closest = -orig.as_dvec3().dot(d);
+    let o = orig.as_dvec3() + t_closest * d;
+    let (big_r, small_r) = (major_radius as f64, minor_radius as f64);
+    if o.length_squared() > (big_r + small_r).powi(2) {
+        return None;
+    }
+
+    // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (x^2 + y^2) with p = o + s d
+    let k = o.dot(d);
+    let g = o.length_squared() + big_r * big_r - small_r * small_r;
+    let four_r2 = 4.0 * big_r * big_r;
+    solve_quartic(
+        1.0,
+        4.0 * k,
+        4.0 * k * k + 2.0 * g - four_r2 * (d.x * d.x + d.y * d.y),
+        4.0 * k * g - 2.0 * four_r2 * (o.x * d.x + o.y * d.y),
+        g * g - four_r2 * (o.x * o.x + o.y * o.y))
+        .into_iter()
+        .map(|s| ((t_closest + s) / length) as f32)
+        .find(|&t| interval.surrounds(t))
+}
+
+// Half the size of the bounding box of a circle with the given normal and radius.
+fn disk_extent(normal: Vec3, radius: f32) -> Vec3 {
+    radius * (Vec3::ONE - normal * normal).max(Vec3::ZERO).powf(0.5)
+}
+
+fn disk_bounding_box(center: Point, normal: Vec3, radius: f32) -> Aabb {
+    let extent = disk_extent(normal, radius);
+    Aabb::from_points(center - extent, center + extent).pad()
+}
+
+// Angle of p around the z axis, in [0, 1].
+fn angle_around_z(p: Point) -> f32 {
+    (p.y.atan2(p.x) + PI) / (2.0 * PI)
+}
+
+// Polar coordinates of p in the disk of the given radius around the z axis: the angle and
+// the distance from the center, both in [0, 1].
+fn polar_uv(p: Point, radius: f32) -> (f32, f32) {
+    (angle_around_z(p), p.truncate().length() / radius)
+}
+
+// Solid angle density of hitting a flat light of the given area at distance t along direction.
+fn area_pdf_value(t: f32, direction: Vec3, normal: Vec3, area: f32) -> f32 {
+    let distance_squared = t * t * direction.length_squared();
+    let cosine = direction.dot(normal).abs() / direction.length();
+    if cosine < 1e-8 { 0.0 } else { distance_squared / (cosine * area) }
+}
+
+// Spherical coordinates of a point p on the unit sphere, both in [0, 1]:
+// u is the angle around the y axis starting from -x, v the angle from -y up to +y.
+fn sphere_uv(p: Point) -> (f32, f32) {
+    let theta = (-p.y).clamp(-1.0, 1.0).acos();
+    let phi = (-p.z).atan2(p.x) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
@@ -0,0 +1,78 @@
+use crate::camera::Color;
+
+// A rendered image: linear (not gamma corrected) colors, stored row by row from the top left.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    // Private, so that there is always one pixel for every position
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    // A black image of the given size.
+    pub fn new(width: u32, height: u32) -> Image {
+        Image { width, height, pixels: vec![Color::ZERO; width as usize * height as usize] }
+    }
+
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<Color>) -> Image {
+        assert_eq!(pixels.len(), width as usize * height as usize, "Pixel count must match the image size.");
+        Image { width, height, pixels }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        assert!(x < self.width && y < self.height, "Pixel ({x}, {y}) is outside the image.");
+        y as usize * self.width as usize + x as usize
+    }
+}
+
+impl Image {
+    // Bilinearly filtered lookup at texture coordinates (u, v), with v = 0 at the bottom row.
+    // u wraps around, v is clamped.
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        if self.pixels.is_empty() {
+            return Color::ZERO;
+        }
+        let x = u.rem_euclid(1.0) * self.width as f32 - 0.5;
+        let y = (1.0 - v.clamp(0.0, 1.0)) * self.height as f32 - 0.5;
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+
+        let wrap_x = |x: f32| (x as i64).rem_euclid(self.width as i64) as u32;
+        let clamp_y = |y: f32| (y.max(0.0) as u32).min(self.height - 1);
+        let (xa, xb) = (wrap_x(x0), wrap_x(x0 + 1.0));
+        let (ya, yb) = (clamp_y(y0), clamp_y(y0 + 1.0));
+
+        let top = self.get(xa, ya).lerp(self.get(xb, ya), fx);
+        let bottom = self.get(xa, yb).lerp(self.get(xb, yb), fx);
+        top.lerp(bottom, fy)
+    }
+}
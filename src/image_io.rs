@@ -0,0 +1,387 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::camera::Color;
+use crate::deflate::zlib_compress;
+use crate::image::Image;
+use crate::tonemap::ToneMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    PpmAscii,  // P3
+    PpmBinary, // P6
+    Png,
+    Pfm,       // linear 32-bit floats, for HDR data
+}
+
+impl ImageFormat {
+    // Picks the format from the file extension. ".ppm" files are written as binary P6.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<ImageFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(ImageFormat::PpmBinary),
+            "png" => Some(ImageFormat::Png),
+            "pfm" => Some(ImageFormat::Pfm),
+            _ => None,
+        }
+    }
+}
+
+// Summary of a written image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SaveReport {
+    // Pixels with a NaN or infinite component, which were written as black.
+    pub invalid_pixels: usize,
+}
+
+// Saves the image to path, in the format given by its extension, with the default tone map.
+pub fn save<P: AsRef<Path>>(image: &Image, path: P) -> io::Result<SaveReport> {
+    save_with(image, path, &ToneMap::default())
+}
+
+pub fn save_with<P: AsRef<Path>>(image: &Image, path: P, tone_map: &ToneMap) -> io::Result<SaveReport> {
+    let format = ImageFormat::from_path(&path).ok_or_else(|| io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Unsupported image extension: {} (expected .ppm, .png or .pfm)", path.as_ref().display()),
+    ))?;
+    save_as(image, path, format, tone_map)
+}
+
+pub fn save_as<P: AsRef<Path>>(image: &Image, path: P, format: ImageFormat, tone_map: &ToneMap) -> io::Result<SaveReport> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let report = write_image(image, &mut file, format, tone_map)?;
+    file.flush()?;
+    Ok(report)
+}
+
+// The tone map is ignored for PFM, which stores the linear values.
+pub fn write_image<W: Write>(image: &Image, out: &mut W, format: ImageFormat, tone_map: &ToneMap) -> io::Result<SaveReport> {
+    match format {
+        ImageFormat::PpmAscii => write_p3(image, out, tone_map),
+        ImageFormat::PpmBinary => write_p6(image, out, tone_map),
+        ImageFormat::Png => write_png(image, out, tone_map),
+        ImageFormat::Pfm => write_pfm(image, out),
+    }
+}
+
+pub fn write_p3<W: Write>(image: &Image, out: &mut W, tone_map: &ToneMap) -> io::Result<SaveReport> {
+    let quantized = tone_map.quantize(image);
+    write!(out, "P3\n{} {}\n255\n", image.width(), image.height())?;
+    for rgb in quantized.rgb.chunks(3) {
+        writeln!(out, "{} {} {}", rgb[0], rgb[1], rgb[2])?;
+    }
+    Ok(SaveReport { invalid_pixels: quantized.invalid_pixels })
+}
+
+pub fn write_p6<W: Write>(image: &Image, out: &mut W, tone_map: &ToneMap) -> io::Result<SaveReport> {
+    let quantized = tone_map.quantize(image);
+    write!(out, "P6\n{} {}\n255\n", image.width(), image.height())?;
+    out.write_all(&quantized.rgb)?;
+    Ok(SaveReport { invalid_pixels: quantized.invalid_pixels })
+}
+
+// Portable float map: little-endian RGB floats, stored bottom row first.
+// Non-finite pixels are written as black, everything else unchanged.
+pub fn write_pfm<W: Write>(image: &Image, out: &mut W) -> io::Result<SaveReport> {
+    write!(out, "PF\n{} {}\n-1.0\n", image.width(), image.height())?;
+    let mut invalid_pixels = 0;
+    let mut bytes = Vec::with_capacity(image.pixels().len() * 12);
+    for row in image.pixels().chunks(image.width().max(1) as usize).rev() {
+        for pixel in row {
+            let pixel = if pixel.is_finite() {
+                *pixel
+            } else {
+                invalid_pixels += 1;
+                Color::ZERO
+            };
+            for c in [pixel.x, pixel.y, pixel.z] {
+                bytes.extend(c.to_le_bytes());
+            }
+        }
+    }
+    out.write_all(&bytes)?;
+    Ok(SaveReport { invalid_pixels })
+}
+
+pub fn write_png<W: Write>(image: &Image, out: &mut W, tone_map: &ToneMap) -> io::Result<SaveReport> {
+    let quantized = tone_map.quantize(image);
+    let rgb = &quantized.rgb;
+    let stride = image.width() as usize * 3;
+
+    // Every scanline is prefixed with the filter that gives the smallest residuals.
+    let mut filtered = Vec::with_capacity((stride + 1) * image.height() as usize);
+    let zero_row = vec![0u8; stride];
+    for (y, row) in rgb.chunks(stride.max(1)).enumerate().take(image.height() as usize) {
+        let above = if y == 0 { &zero_row[..] } else { &rgb[(y - 1) * stride..y * stride] };
+        let (filter, line) = (0..5u8)
+            .map(|filter| (filter, filter_scanline(filter, row, above)))
+            .min_by_key(|(_, line)| line.iter().map(|&b| (b as i8).unsigned_abs() as u32).sum::<u32>())
+            .expect("There are five filters to pick from.");
+        filtered.push(filter);
+        filtered.extend(line);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend(image.width().to_be_bytes());
+    header.extend(image.height().to_be_bytes());
+    header.extend([8, 2, 0, 0, 0]); // 8 bit depth, RGB, deflate, adaptive filtering, no interlace
+
+    out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+    write_png_chunk(out, b"IHDR", &header)?;
+    write_png_chunk(out, b"IDAT", &zlib_compress(&filtered))?;
+    write_png_chunk(out, b"IEND", &[])?;
+    Ok(SaveReport { invalid_pixels: quantized.invalid_pixels })
+}
+
+fn filter_scanline(filter: u8, row: &[u8], above: &[u8]) -> Vec<u8> {
+    const BPP: usize = 3;
+    (0..row.len())
+        .map(|i| {
+            let a = if i >= BPP { row[i - BPP] } else { 0 };
+            let b = above[i];
+            let c = if i >= BPP { above[i - BPP] } else { 0 };
+            let predictor = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                _ => paeth(a, b, c),
+            };
+            row[i].wrapping_sub(predictor)
+        })
+        .collect()
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc { a } else if pb <= pc { b } else { c }
+}
+
+fn write_png_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    out.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// Loads a linear image from a Radiance HDR (.hdr), PFM (.pfm) or PPM (.ppm) file.
+// PPM values are gamma decoded with the default tone map.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Image> {
+    let extension = path.as_ref().extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let bytes = std::fs::read(&path)?;
+    let image = match extension.as_deref() {
+        Some("hdr") | Some("pic") => read_hdr(&bytes),
+        Some("pfm") => read_pfm(&bytes),
+        Some("ppm") => read_ppm(&bytes),
+        _ => Err(invalid_data("unsupported image extension (expected .hdr, .pfm or .ppm)")),
+    };
+    image.map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.as_ref().display(), e)))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// Splits off the next whitespace separated header token, skipping '#' comments.
+fn next_token<'a>(bytes: &mut &'a [u8]) -> io::Result<&'a str> {
+    loop {
+        let start = bytes.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(bytes.len());
+        *bytes = &bytes[start..];
+        if bytes.first() == Some(&b'#') {
+            let end = bytes.iter().position(|&b| b == b'\n').unwrap_or(bytes.len());
+            *bytes = &bytes[end..];
+        } else {
+            break;
+        }
+    }
+    let end = bytes.iter().position(|b| b.is_ascii_whitespace()).unwrap_or(bytes.len());
+    let (token, rest) = bytes.split_at(end);
+    // A single whitespace byte separates the header from binary data.
+    *bytes = if rest.is_empty() { rest } else { &rest[1..] };
+    std::str::from_utf8(token).map_err(|_| invalid_data("header is not ASCII"))
+}
+
+fn next_number<T: std::str::FromStr>(bytes: &mut &[u8]) -> io::Result<T> {
+    let token = next_token(bytes)?;
+    token.parse().map_err(|_| invalid_data(&format!("invalid header value '{}'", token)))
+}
+
+pub fn read_pfm(bytes: &[u8]) -> io::Result<Image> {
+    let mut rest = bytes;
+    let channels = match next_token(&mut rest)? {
+        "PF" => 3,
+        "Pf" => 1,
+        _ => return Err(invalid_data("not a PFM file")),
+    };
+    let width: u32 = next_number(&mut rest)?;
+    let height: u32 = next_number(&mut rest)?;
+    let scale: f32 = next_number(&mut rest)?;
+
+    let size = (width as usize).checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(channels * 4))
+        .ok_or_else(|| invalid_data("PFM image is too large"))?;
+    if rest.len() < size {
+        return Err(invalid_data("PFM data is truncated"));
+    }
+    let values = rest[..size].chunks(4)
+        .map(|b| {
+            let b = [b[0], b[1], b[2], b[3]];
+            if scale < 0.0 { f32::from_le_bytes(b) } else { f32::from_be_bytes(b) }
+        })
+        .collect::<Vec<f32>>();
+
+    // Rows are stored bottom to top.
+    let pixels = values.chunks(width.max(1) as usize * channels)
+        .rev()
+        .flat_map(|row| row.chunks(channels))
+        .map(|c| if channels == 3 { Color::new(c[0], c[1], c[2]) } else { Color::splat(c[0]) })
+        .collect();
+    Ok(Image::from_pixels(width, height, pixels))
+}
+
+pub fn read_ppm(bytes: &[u8]) -> io::Result<Image> {
+    let mut rest = bytes;
+    let binary = match next_token(&mut rest)? {
+        "P3" => false,
+        "P6" => true,
+        _ => return Err(invalid_data("not a P3 or P6 PPM file")),
+    };
+    let width: u32 = next_number(&mut rest)?;
+    let height: u32 = next_number(&mut rest)?;
+    let max_value: u32 = next_number(&mut rest)?;
+    if max_value == 0 || max_value > 255 {
+        return Err(invalid_data("only 8-bit PPM files are supported"));
+    }
+
+    let count = (width as usize).checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(3))
+        .ok_or_else(|| invalid_data("PPM image is too large"))?;
+    let values = if binary {
+        rest.get(..count).ok_or_else(|| invalid_data("PPM data is truncated"))?.to_vec()
+    } else {
+        (0..count).map(|_| next_number::<u8>(&mut rest)).collect::<io::Result<Vec<u8>>>()?
+    };
+
+    let tone_map = ToneMap::default();
+    let pixels = values.chunks(3)
+        .map(|c| Color::new(c[0] as f32, c[1] as f32, c[2] as f32) / max_value as f32)
+        .map(|c| tone_map.unmap(&c))
+        .collect();
+    Ok(Image::from_pixels(width, height, pixels))
+}
+
+// Radiance RGBE file, flat or with run-length encoded scanlines.
+pub fn read_hdr(bytes: &[u8]) -> io::Result<Image> {
+    let mut rest = bytes;
+    let mut line = || -> io::Result<&str> {
+        let end = rest.iter().position(|&b| b == b'\n').ok_or_else(|| invalid_data("HDR header is truncated"))?;
+        let text = std::str::from_utf8(&rest[..end]).map_err(|_| invalid_data("HDR header is not ASCII"))?;
+        rest = &rest[end + 1..];
+        Ok(text)
+    };
+
+    if !line()?.starts_with("#?") {
+        return Err(invalid_data("not a Radiance HDR file"));
+    }
+    loop {
+        let header_line = line()?;
+        if header_line.is_empty() {
+            break;
+        }
+        if let Some(format) = header_line.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(invalid_data(&format!("unsupported HDR pixel format {}", format)));
+            }
+        }
+    }
+    let resolution = line()?.split_whitespace().collect::<Vec<&str>>();
+    let (height, width) = match resolution.as_slice() {
+        ["-Y", h, "+X", w] => (
+            h.parse::<u32>().map_err(|_| invalid_data("invalid HDR height"))?,
+            w.parse::<u32>().map_err(|_| invalid_data("invalid HDR width"))?,
+        ),
+        _ => return Err(invalid_data("only '-Y height +X width' HDR orientation is supported")),
+    };
+    if width == 0 || height == 0 {
+        return Err(invalid_data("HDR image is empty"));
+    }
+
+    // Every pixel takes at least one byte, which bounds the allocation for bogus headers.
+    let capacity = (width as usize * height as usize).min(rest.len());
+    let mut data = rest;
+    let mut take = |n: usize| -> io::Result<&[u8]> {
+        let (head, tail) = data.split_at_checked(n).ok_or_else(|| invalid_data("HDR data is truncated"))?;
+        data = tail;
+        Ok(head)
+    };
+
+    let width_usize = width as usize;
+    let mut pixels = Vec::with_capacity(capacity);
+    let mut scanline = vec![[0u8; 4]; width_usize];
+    for _ in 0..height {
+        let start = take(4)?;
+        let rle = (8..0x8000).contains(&width_usize) && start[0] == 2 && start[1] == 2 && start[2] & 0x80 == 0;
+        if rle {
+            if ((start[2] as usize) << 8 | start[3] as usize) != width_usize {
+                return Err(invalid_data("HDR scanline width mismatch"));
+            }
+            // Each of the four channels is run-length encoded separately.
+            for channel in 0..4 {
+                let mut x = 0;
+                while x < width_usize {
+                    let count = take(1)?[0] as usize;
+                    if count > 128 {
+                        let run = count - 128;
+                        let value = take(1)?[0];
+                        scanline.get_mut(x..x + run).ok_or_else(|| invalid_data("HDR run overflows scanline"))?
+                            .iter_mut().for_each(|p| p[channel] = value);
+                        x += run;
+                    } else {
+                        if count == 0 {
+                            return Err(invalid_data("invalid HDR run length"));
+                        }
+                        let values = take(count)?;
+                        scanline.get_mut(x..x + count).ok_or_else(|| invalid_data("HDR run overflows scanline"))?
+                            .iter_mut().zip(values).for_each(|(p, &v)| p[channel] = v);
+                        x += count;
+                    }
+                }
+            }
+        } else {
+            scanline[0] = [start[0], start[1], start[2], start[3]];
+            for pixel in scanline.iter_mut().skip(1) {
+                let p = take(4)?;
+                *pixel = [p[0], p[1], p[2], p[3]];
+            }
+        }
+        pixels.extend(scanline.iter().map(|&rgbe| rgbe_to_color(rgbe)));
+    }
+    Ok(Image::from_pixels(width, height, pixels))
+}
+
+fn rgbe_to_color([r, g, b, e]: [u8; 4]) -> Color {
+    if e == 0 {
+        return Color::ZERO;
+    }
+    let f = 2f32.powi(e as i32 - (128 + 8));
+    Color::new(r as f32 + 0.5, g as f32 + 0.5, b as f32 + 0.5) * f
+}
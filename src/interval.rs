@@ -1,13 +1,36 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Interval {
-    min: f32,
-    max: f32,
+    pub min: f32,
+    pub max: f32,
 }
 
 impl Interval {
+    pub const EMPTY: Interval = Interval { min: f32::INFINITY, max: f32::NEG_INFINITY };
+    pub const UNIVERSE: Interval = Interval { min: f32::NEG_INFINITY, max: f32::INFINITY };
+
     pub fn new(min: f32, max: f32) -> Interval {
         Interval { min, max }
     }
 
+    // Smallest interval enclosing both a and b.
+    pub fn enclosing(a: Interval, b: Interval) -> Interval {
+        Interval { min: a.min.min(b.min), max: a.max.max(b.max) }
+    }
+
+    // Largest interval inside both a and b; empty if they don't overlap.
+    pub fn overlap(a: Interval, b: Interval) -> Interval {
+        Interval { min: a.min.max(b.min), max: a.max.min(b.max) }
+    }
+
+    pub fn size(&self) -> f32 {
+        self.max - self.min
+    }
+
+    pub fn expand(&self, delta: f32) -> Interval {
+        let padding = delta / 2.0;
+        Interval { min: self.min - padding, max: self.max + padding }
+    }
+
     pub fn surrounds(&self, x:f32) -> bool {
         self.min < x && x < self.max
     }
@@ -37,4 +60,4 @@ impl Interval {
         else if x > self.max {self.max }
         else { x }
     }
-}
\ No newline at end of file
+}
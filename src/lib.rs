@@ -1,5 +1,23 @@
+pub mod aabb;
+pub mod background;
+pub mod bvh;
 pub mod camera;
+pub mod csg;
+mod deflate;
 pub mod hittable;
+pub mod image;
+pub mod image_io;
 pub mod material;
-mod ray;
-mod interval;
\ No newline at end of file
+pub mod mesh;
+pub mod microfacet;
+pub mod pdf;
+pub mod progressive;
+pub mod ray;
+pub mod render;
+pub mod roots;
+pub mod scene;
+pub mod spectrum;
+pub mod texture;
+pub mod tiles;
+pub mod tonemap;
+pub mod interval;
@@ -1,4 +1,197 @@
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-fn main() {
-    println!("hello world")
+use ray_tracing::bvh::Bvh;
+use ray_tracing::image_io::{self, ImageFormat};
+use ray_tracing::render::RenderOptions;
+use ray_tracing::scene::{read_scene, SceneError};
+use ray_tracing::tiles::Checkpoint;
+
+const USAGE: &str = "\
+Usage: ray_tracing <scene> <output> [options]
+
+Renders a scene file to an image. The output format follows its extension
+(.png, .ppm or .pfm).
+
+Options:
+  --width <pixels>     image width (the height follows the scene's aspect ratio)
+  --samples <count>    samples per pixel
+  --depth <bounces>    maximum ray depth
+  --seed <number>      seed for the random sampling (default: 0)
+  --threads <count>    number of render threads (default: all cores)
+  --spectral           trace one wavelength per path, for dispersion
+  --checkpoint <file>  save progress to <file>, and resume from it if it exists
+  --checkpoint-interval <seconds>
+                       time between checkpoint saves (default: 60)
+  -h, --help           show this message";
+
+#[derive(Default)]
+struct Args {
+    scene: PathBuf,
+    output: PathBuf,
+    width: Option<u32>,
+    samples: Option<u32>,
+    depth: Option<u32>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+    spectral: bool,
+    checkpoint: Option<PathBuf>,
+    checkpoint_interval: Option<u64>,
+}
+
+enum ArgsError {
+    Help,
+    Invalid(String),
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, ArgsError> {
+    fn value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, ArgsError> {
+        let value = value.ok_or_else(|| ArgsError::Invalid(format!("{} needs a value", flag)))?;
+        value.parse().map_err(|_| ArgsError::Invalid(format!("invalid value '{}' for {}", value, flag)))
+    }
+
+    let mut parsed = Args::default();
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(ArgsError::Help),
+            "--width" => parsed.width = Some(value(&arg, args.next())?),
+            "--samples" => parsed.samples = Some(value(&arg, args.next())?),
+            "--depth" => parsed.depth = Some(value(&arg, args.next())?),
+            "--seed" => parsed.seed = Some(value(&arg, args.next())?),
+            "--threads" => parsed.threads = Some(value(&arg, args.next())?),
+            "--spectral" => parsed.spectral = true,
+            "--checkpoint" => parsed.checkpoint = Some(value(&arg, args.next())?),
+            "--checkpoint-interval" => parsed.checkpoint_interval = Some(value(&arg, args.next())?),
+            flag if flag.starts_with("--") => return Err(ArgsError::Invalid(format!("unknown option {}", flag))),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    match <[PathBuf; 2]>::try_from(positional) {
+        Ok([scene, output]) => {
+            parsed.scene = scene;
+            parsed.output = output;
+        }
+        Err(positional) => return Err(ArgsError::Invalid(format!(
+            "expected a scene and an output path, got {} argument(s)", positional.len()))),
+    }
+    if parsed.width == Some(0) || parsed.samples == Some(0) || parsed.threads == Some(0) {
+        return Err(ArgsError::Invalid("--width, --samples and --threads must be at least 1".to_string()));
+    }
+    if parsed.checkpoint_interval.is_some() && parsed.checkpoint.is_none() {
+        return Err(ArgsError::Invalid("--checkpoint-interval needs --checkpoint".to_string()));
+    }
+    Ok(parsed)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    if ImageFormat::from_path(&args.output).is_none() {
+        return Err(format!("{}: unsupported output format (expected .png, .ppm or .pfm)", args.output.display()));
+    }
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|e| format!("cannot set up {} threads: {}", threads, e))?;
+    }
+
+    let (mut camera, world) = read_scene(&args.scene).map_err(|e| match e {
+        // I/O errors already name the file they failed on
+        SceneError::Io(_) => e.to_string(),
+        SceneError::Parse { .. } => format!("{}: {}", args.scene.display(), e),
+    })?;
+    if let Some(width) = args.width {
+        camera.set_image_width(width);
+    }
+    if let Some(samples) = args.samples {
+        camera.set_samples_per_pixel(samples);
+    }
+    if let Some(depth) = args.depth {
+        camera.set_max_depth(depth);
+    }
+    if let Some(seed) = args.seed {
+        camera.set_seed(seed);
+    }
+    if args.spectral {
+        camera.set_spectral(true);
+    }
+    let camera = camera.build();
+
+    // Fail before spending time on the render if the output cannot be written
+    check_writable(&args.output).map_err(|e| format!("{}: cannot write output: {}", args.output.display(), e))?;
+
+    let start = Instant::now();
+    let mut options = RenderOptions::default();
+    #[cfg(feature = "progress-bar")]
+    {
+        options.on_progress = Some(ray_tracing::render::progress_bar());
+    }
+    if let Some(path) = args.checkpoint {
+        let mut checkpoint = Checkpoint::new(path);
+        if let Some(seconds) = args.checkpoint_interval {
+            checkpoint.interval = Duration::from_secs(seconds);
+        }
+        // Progress saved for an earlier version of the scene file is not resumed. Files the
+        // scene refers to, like meshes and textures, are not part of the key.
+        let source = std::fs::read(&args.scene).map_err(|e| format!("{}: {}", args.scene.display(), e))?;
+        checkpoint.key = fnv1a(&source);
+        options.checkpoint = Some(checkpoint);
+    }
+    let image = camera.render_with(&Bvh::new(world), &options).map_err(|e| match &options.checkpoint {
+        Some(checkpoint) => format!("{}: checkpoint failed: {}", checkpoint.path.display(), e),
+        None => e.to_string(),
+    })?;
+    eprintln!("Rendered {}x{} in {:.2?}", image.width(), image.height(), start.elapsed());
+
+    let report = image_io::save(&image, &args.output)
+        .map_err(|e| format!("{}: cannot write output: {}", args.output.display(), e))?;
+    if report.invalid_pixels > 0 {
+        eprintln!("warning: {} pixel(s) were NaN or infinite and were written as black", report.invalid_pixels);
+    }
+    Ok(())
+}
+
+// Checks that the file can be written without touching it. An existing file keeps its
+// contents until there is an image to replace it with, and a missing one is not created, so
+// a render that fails later leaves nothing behind.
+fn check_writable(path: &Path) -> io::Result<()> {
+    if path.exists() {
+        return OpenOptions::new().write(true).open(path).map(drop);
+    }
+    // Tries a file of another name in the same directory instead
+    let mut probe = path.as_os_str().to_owned();
+    probe.push(".probe");
+    File::create(&probe)?;
+    fs::remove_file(&probe)
+}
+
+// FNV-1a hash, stable across builds unlike the standard library's hasher.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(ArgsError::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(ArgsError::Invalid(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
 }
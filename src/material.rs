@@ -1,79 +1,219 @@
+use std::f32::consts::PI;
+
 use glam::{vec3, Vec3};
 use rand::prelude::*;
 
-use crate::{ray::Ray, hittable::HitRecord, camera::Color};
+use crate::{ray::Ray, hittable::HitRecord, camera::Color, texture::Texture};
+use crate::microfacet;
+use crate::pdf::{Onb, Pdf};
 
 #[derive(Clone)]
 pub enum Material {
-    Lambertian(Color), // color = "albedo"
-    Metal(Color, f32), // albedo, fuzz
-    Dielectric(f32) // index of refraction
+    Lambertian(Texture), // texture = "albedo"
+    Metal(Texture, f32), // albedo, fuzz
+    Dielectric(Ior), // index of refraction
+    Glass(Ior, Color), // index of refraction, absorption per unit of distance inside
+    DiffuseLight(Color), // emitted radiance
+    Isotropic(Texture), // albedo, scatters the same in every direction (for volumes)
+    Microfacet(Texture, f32, f32), // base color, roughness, metalness (all in [0, 1])
 }
 
 impl Material {
-    pub fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Ray, Color) > {
+    pub fn scatter<R: Rng>(&self, ray: &Ray, hit_record: &HitRecord, rng: &mut R) -> Option<(Ray, Color) > {
         use Material::*;
         match &self {
             Lambertian(albedo) => {
-                let mut scatter_direction = hit_record.normal + random_unit_vector();
+                let mut scatter_direction = hit_record.normal + random_unit_vector(rng);
 
                 // To protect from degenerate (near zero) scatter directions
                 if scatter_direction.length() <= 1e-8 {
                     scatter_direction = hit_record.normal;
                 }
 
-                let scattered_ray = Ray::new(hit_record.point, scatter_direction);
+                let scattered_ray = ray.scattered(hit_record.point, scatter_direction);
 
-                let attenuation = *albedo;
+                let attenuation = albedo.value(hit_record.u, hit_record.v, hit_record.point);
                 Some((scattered_ray, attenuation))
             },
             Metal(albedo, fuzz) => {
                 let reflection_dir = reflect(ray.dir.normalize(), hit_record.normal);
-                let scattered_ray = Ray::new(hit_record.point, reflection_dir + *fuzz * random_unit_vector());
+                let scattered_ray = ray.scattered(hit_record.point, reflection_dir + *fuzz * random_unit_vector(rng));
                 
                 if scattered_ray.dir.dot(hit_record.normal) > 0.0 { 
-                    Some((scattered_ray, *albedo))
+                    Some((scattered_ray, albedo.value(hit_record.u, hit_record.v, hit_record.point)))
                 } else {
                     None // fuzz may result in invalid rays inside of sphere
                 }
             },
             Dielectric(ir) => {
                 let attenuation = Color::new(1.0, 1.0, 1.0);
-
-                let refraction_ratio = if hit_record.front_face {
-                    1.0 / *ir
+                Some((refract_or_reflect(ray, hit_record, ir.at(ray.wavelength), rng), attenuation))
+            },
+            Glass(ir, absorption) => {
+                // A ray hitting the inside of the surface travelled through the glass.
+                let attenuation = if hit_record.front_face {
+                    Color::ONE
                 } else {
-                    *ir
+                    let distance = hit_record.t * ray.dir.length();
+                    (-*absorption * distance).exp()
                 };
+                Some((refract_or_reflect(ray, hit_record, ir.at(ray.wavelength), rng), attenuation))
+            },
+            DiffuseLight(_) => None,
+            Isotropic(albedo) => {
+                let scattered_ray = ray.scattered(hit_record.point, random_unit_vector(rng));
+                Some((scattered_ray, albedo.value(hit_record.u, hit_record.v, hit_record.point)))
+            },
+            Microfacet(..) => {
+                let pdf = self.scattering_pdf(ray, hit_record)?;
+                let direction = pdf.generate(rng);
+                let pdf_value = pdf.value(direction);
+                if pdf_value <= 0.0 {
+                    return None;
+                }
+                let scattered_ray = ray.scattered(hit_record.point, direction);
+                Some((scattered_ray, self.eval(ray, hit_record, direction) / pdf_value))
+            },
+        }
+    }
 
-                let unit_direction = ray.dir.normalize();
+    // Glass that lets through the fraction tint of the light after distance in it.
+    pub fn tinted_glass<I: Into<Ior>>(ir: I, tint: Color, distance: f32) -> Material {
+        let tint = tint.clamp(Color::splat(1e-6), Color::ONE);
+        let absorption = -Color::new(tint.x.ln(), tint.y.ln(), tint.z.ln()) / distance;
+        Material::Glass(ir.into(), absorption)
+    }
 
-                let normal_dot_uiv = (-unit_direction).dot(hit_record.normal);
-                let cos_theta = if normal_dot_uiv > 1.0 {
-                    1.0
-                } else {
-                    normal_dot_uiv
-                };
-                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
-                let cannot_refract = refraction_ratio * sin_theta > 1.0;
-                let schlick_reflect = reflectance(cos_theta, refraction_ratio) > rand::thread_rng().gen::<f32>();
+    // Density the material would like its scattered directions drawn from, or None for
+    // materials that scatter into a few exact directions (mirrors, glass), which only
+    // scatter can sample.
+    pub fn scattering_pdf(&self, ray: &Ray, hit_record: &HitRecord) -> Option<Pdf<'static>> {
+        match &self {
+            Material::Lambertian(_) => Some(Pdf::Cosine(Onb::new(hit_record.normal))),
+            Material::Isotropic(_) => Some(Pdf::Sphere),
+            Material::Microfacet(..) => {
+                let onb = Onb::new(hit_record.normal);
+                let wo = onb.to_local(-ray.dir.normalize());
+                self.microfacet(hit_record).map(|model| Pdf::Microfacet { model, onb, wo })
+            }
+            _ => None,
+        }
+    }
 
-                let direction = if cannot_refract || schlick_reflect {
-                    reflect(unit_direction, hit_record.normal)
-                } else {
-                    refract(unit_direction, hit_record.normal, refraction_ratio)
-                };
+    // Fraction of the light arriving from direction that scatters along the incoming ray
+    // back to its origin: the BRDF times the cosine term. Zero for materials without a
+    // scattering_pdf.
+    pub fn eval(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        match &self {
+            Material::Lambertian(albedo) => {
+                let cosine = hit_record.normal.dot(direction.normalize()).max(0.0);
+                albedo.value(hit_record.u, hit_record.v, hit_record.point) * cosine / PI
+            }
+            Material::Isotropic(albedo) => albedo.value(hit_record.u, hit_record.v, hit_record.point) / (4.0 * PI),
+            Material::Microfacet(..) => {
+                let onb = Onb::new(hit_record.normal);
+                let wo = onb.to_local(-ray.dir.normalize());
+                let wi = onb.to_local(direction.normalize());
+                self.microfacet(hit_record).map_or(Color::ZERO, |model| model.eval(wo, wi))
+            }
+            _ => Color::ZERO,
+        }
+    }
 
-                let scattered_ray = Ray::new(hit_record.point, direction);
+    // The microfacet model at the hit point, for Microfacet materials.
+    fn microfacet(&self, hit_record: &HitRecord) -> Option<microfacet::Microfacet> {
+        match &self {
+            Material::Microfacet(base_color, roughness, metalness) => {
+                let base_color = base_color.value(hit_record.u, hit_record.v, hit_record.point);
+                Some(microfacet::Microfacet::new(base_color, *roughness, *metalness))
+            }
+            _ => None,
+        }
+    }
 
-                Some((scattered_ray, attenuation))
+    // Light given off at the hit point, added to whatever is scattered.
+    pub fn emitted(&self, _hit_record: &HitRecord) -> Color {
+        match &self {
+            Material::DiffuseLight(emit) => *emit,
+            _ => Color::ZERO,
+        }
+    }
+}
+
+// Index of refraction, which may depend on the wavelength (dispersion). Wavelengths are in
+// nm; the Cauchy and Sellmeier coefficients use micrometers, like the published ones.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ior {
+    Constant(f32),
+    // n = a + b / wavelength^2
+    Cauchy { a: f32, b: f32 },
+    // n^2 = 1 + sum of b[i] wavelength^2 / (wavelength^2 - c[i])
+    Sellmeier { b: [f32; 3], c: [f32; 3] },
+}
+
+impl Ior {
+    // Borosilicate crown glass, the most common optical glass
+    pub const BK7: Ior = Ior::Sellmeier {
+        b: [1.039_612, 0.231_792_3, 1.010_469_5],
+        c: [0.006_000_7, 0.020_017_9, 103.560_65],
+    };
+    pub const DIAMOND: Ior = Ior::Sellmeier { b: [4.3356, 0.3306, 0.0], c: [0.011_236, 0.030_625, 0.0] };
+
+    // Wavelength that RGB renders use, the sodium D line
+    pub const NOMINAL_WAVELENGTH: f32 = 587.6;
+
+    pub fn at(&self, wavelength: Option<f32>) -> f32 {
+        let micrometers = wavelength.unwrap_or(Self::NOMINAL_WAVELENGTH) / 1000.0;
+        let l2 = micrometers * micrometers;
+        match *self {
+            Ior::Constant(n) => n,
+            Ior::Cauchy { a, b } => a + b / l2,
+            Ior::Sellmeier { b, c } => {
+                let sum = b.iter().zip(c).map(|(b, c)| b * l2 / (l2 - c)).sum::<f32>();
+                (1.0 + sum).sqrt()
             }
         }
     }
 }
 
-fn random_unit_vector() -> Vec3 {
-   random_in_unit_sphere().normalize()
+impl From<f32> for Ior {
+    fn from(n: f32) -> Ior {
+        Ior::Constant(n)
+    }
+}
+
+// Refracted ray through a surface with index of refraction ir (relative to the outside),
+// or the reflected one if it cannot refract or by chance of the Fresnel reflectance.
+fn refract_or_reflect<R: Rng>(ray: &Ray, hit_record: &HitRecord, ir: f32, rng: &mut R) -> Ray {
+    let refraction_ratio = if hit_record.front_face {
+        1.0 / ir
+    } else {
+        ir
+    };
+
+    let unit_direction = ray.dir.normalize();
+
+    let normal_dot_uiv = (-unit_direction).dot(hit_record.normal);
+    let cos_theta = if normal_dot_uiv > 1.0 {
+        1.0
+    } else {
+        normal_dot_uiv
+    };
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let cannot_refract = refraction_ratio * sin_theta > 1.0;
+    let schlick_reflect = reflectance(cos_theta, refraction_ratio) > rng.gen::<f32>();
+
+    let direction = if cannot_refract || schlick_reflect {
+        reflect(unit_direction, hit_record.normal)
+    } else {
+        refract(unit_direction, hit_record.normal, refraction_ratio)
+    };
+
+    ray.scattered(hit_record.point, direction)
+}
+
+fn random_unit_vector<R: Rng>(rng: &mut R) -> Vec3 {
+   random_in_unit_sphere(rng).normalize()
 }
 
 // fn random_on_hemisphere(normal: Vec3) -> Vec3 {
@@ -85,17 +225,16 @@ fn random_unit_vector() -> Vec3 {
 //     }
 // }
 
-pub fn random_vec() -> Vec3 {
-    let mut rng = rand::thread_rng();
+pub fn random_vec<R: Rng>(rng: &mut R) -> Vec3 {
     vec3(
         rng.gen_range(-1.0..=1.0),
         rng.gen_range(-1.0..=1.0),
         rng.gen_range(-1.0..=1.0))
 }
 
-fn random_in_unit_sphere() -> Vec3 {
+fn random_in_unit_sphere<R: Rng>(rng: &mut R) -> Vec3 {
     loop {
-        let vec = random_vec();
+        let vec = random_vec(rng);
 
         if vec.length_squared() < 1.0 {
             break vec;
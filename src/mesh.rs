@@ -0,0 +1,228 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use glam::{Vec2, Vec3};
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::hittable::HitRecord;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::ray::{Point, Ray};
+
+#[derive(Clone, Debug)]
+pub struct Triangle {
+    pub vertices: [Point; 3],
+    // Per-vertex shading normals, interpolated across the face when present.
+    pub normals: Option<[Vec3; 3]>,
+    // Per-vertex texture coordinates. Without them the barycentric coordinates are used.
+    pub uvs: Option<[Vec2; 3]>,
+}
+
+impl Triangle {
+    pub fn new(a: Point, b: Point, c: Point) -> Triangle {
+        Triangle { vertices: [a, b, c], normals: None, uvs: None }
+    }
+
+    // Möller–Trumbore intersection.
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<HitRecord> {
+        let [a, b, c] = self.vertices;
+        let edge1 = b - a;
+        let edge2 = c - a;
+
+        let p = ray.dir.cross(edge2);
+        let det = edge1.dot(p);
+        // No hit if the ray is parallel to the triangle.
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let s = ray.orig - a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = ray.dir.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = interval.surround_where(edge2.dot(q) * inv_det)?;
+        let w = 1.0 - u - v;
+
+        let uv = match self.uvs {
+            Some([uv_a, uv_b, uv_c]) => {
+                let uv = w * uv_a + u * uv_b + v * uv_c;
+                (uv.x, uv.y)
+            }
+            None => (u, v),
+        };
+
+        let geometric_normal = edge1.cross(edge2).normalize();
+        let mut hit_record = HitRecord::new_from_ray(geometric_normal, t, uv, ray);
+        if let Some([n_a, n_b, n_c]) = self.normals {
+            // Keep the shading normal on the same side as the geometric one.
+            let shading_normal = (w * n_a + u * n_b + v * n_c).normalize_or_zero();
+            if shading_normal != Vec3::ZERO {
+                let side = if hit_record.front_face { 1.0 } else { -1.0 };
+                hit_record.normal = if shading_normal.dot(geometric_normal) < 0.0 {
+                    -side * shading_normal
+                } else {
+                    side * shading_normal
+                };
+            }
+        }
+        Some(hit_record)
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        let [a, b, c] = self.vertices;
+        Aabb::enclosing(Aabb::from_points(a, b), Aabb::from_points(b, c)).pad()
+    }
+}
+
+// Triangle mesh sharing a single material, with its own BVH over the triangles.
+pub struct Mesh {
+    triangles: Bvh<Triangle>,
+    material: Material,
+}
+
+impl Mesh {
+    pub fn new(triangles: Vec<Triangle>, material: &Material) -> Mesh {
+        Mesh { triangles: Bvh::build(triangles, Triangle::bounding_box), material: material.clone() }
+    }
+
+    // Loads the faces of a Wavefront OBJ file. Polygons are split into triangle fans,
+    // and groups, smoothing and material statements are ignored.
+    pub fn load_obj<P: AsRef<Path>>(path: P, material: &Material) -> io::Result<Mesh> {
+        let source = fs::read_to_string(&path)?;
+        let triangles = parse_obj(&source).map_err(|message| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}:{}", path.as_ref().display(), message),
+        ))?;
+        Ok(Mesh::new(triangles, material))
+    }
+
+    pub fn triangles(&self) -> &[Triangle] {
+        self.triangles.items()
+    }
+
+    pub fn hit(&self, ray: &Ray, interval: Interval) -> Option<(HitRecord, &Material)> {
+        self.triangles
+            .traverse(ray, interval, |triangle, ray, interval| triangle.hit(ray, interval).map(|hr| (hr, ())))
+            .map(|(hit_record, _)| (hit_record, &self.material))
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        self.triangles.bounding_box()
+    }
+}
+
+// Parses OBJ source into triangles. Errors are prefixed with their line number.
+fn parse_obj(source: &str) -> Result<Vec<Triangle>, String> {
+    let mut positions: Vec<Point> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut triangles = Vec::new();
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let error = |message: String| format!("{}: {}", line_number, message);
+
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args = tokens.collect::<Vec<&str>>();
+
+        match keyword {
+            "v" => positions.push(parse_floats::<3>(&args).map(Vec3::from_array).map_err(error)?),
+            "vn" => normals.push(parse_floats::<3>(&args).map(Vec3::from_array).map_err(error)?),
+            "vt" => uvs.push(parse_uv(&args).map_err(error)?),
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(format!("face needs at least 3 vertices, found {}", args.len())));
+                }
+                let corners = args.iter()
+                    .map(|arg| parse_face_vertex(arg, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<FaceVertex>, String>>()
+                    .map_err(error)?;
+
+                for i in 1..corners.len() - 1 {
+                    let fan = [corners[0], corners[i], corners[i + 1]];
+                    let mut triangle = Triangle::new(
+                        positions[fan[0].position],
+                        positions[fan[1].position],
+                        positions[fan[2].position]);
+                    if let [Some(a), Some(b), Some(c)] = fan.map(|fv| fv.uv) {
+                        triangle.uvs = Some([uvs[a], uvs[b], uvs[c]]);
+                    }
+                    if let [Some(a), Some(b), Some(c)] = fan.map(|fv| fv.normal) {
+                        triangle.normals = Some([normals[a], normals[b], normals[c]]);
+                    }
+                    triangles.push(triangle);
+                }
+            }
+            _ => {} // o, g, s, usemtl, mtllib, ...
+        }
+    }
+    Ok(triangles)
+}
+
+#[derive(Clone, Copy)]
+struct FaceVertex {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+fn parse_floats<const N: usize>(args: &[&str]) -> Result<[f32; N], String> {
+    // Extra components (like the optional w of positions) are ignored.
+    if args.len() < N {
+        return Err(format!("expected {} numbers, found {}", N, args.len()));
+    }
+    let mut values = [0.0; N];
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = arg.parse().map_err(|_| format!("invalid number '{}'", arg))?;
+    }
+    Ok(values)
+}
+
+// Texture coordinates are "u [v [w]]". A missing v is 0, and the w of 3D textures is ignored.
+fn parse_uv(args: &[&str]) -> Result<Vec2, String> {
+    if args.is_empty() || args.len() > 3 {
+        return Err(format!("expected 1 to 3 numbers, found {}", args.len()));
+    }
+    let values = args.iter()
+        .map(|arg| arg.parse().map_err(|_| format!("invalid number '{}'", arg)))
+        .collect::<Result<Vec<f32>, String>>()?;
+    Ok(Vec2::new(values[0], values.get(1).copied().unwrap_or(0.0)))
+}
+
+// Parses "v", "v/vt", "v//vn" or "v/vt/vn", with 1-based or negative (relative) indices.
+fn parse_face_vertex(arg: &str, position_count: usize, uv_count: usize, normal_count: usize) -> Result<FaceVertex, String> {
+    let mut parts = arg.split('/');
+    let index = |part: Option<&str>, count: usize, kind: &str| -> Result<Option<usize>, String> {
+        match part {
+            None | Some("") => Ok(None),
+            Some(text) => {
+                let i: i64 = text.parse().map_err(|_| format!("invalid {} index '{}'", kind, text))?;
+                let resolved = if i < 0 { count as i64 + i } else { i - 1 };
+                if i == 0 || resolved < 0 || resolved >= count as i64 {
+                    return Err(format!("{} index {} out of range (have {})", kind, i, count));
+                }
+                Ok(Some(resolved as usize))
+            }
+        }
+    };
+
+    let position = index(parts.next(), position_count, "vertex")?
+        .ok_or_else(|| format!("missing vertex index in '{}'", arg))?;
+    let uv = index(parts.next(), uv_count, "texture")?;
+    let normal = index(parts.next(), normal_count, "normal")?;
+    Ok(FaceVertex { position, uv, normal })
+}
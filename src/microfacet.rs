@@ -0,0 +1,116 @@
+// GGX (Trowbridge-Reitz) microfacet model with height-correlated Smith masking, in the
+// metallic-roughness parametrization of common PBR tools. All directions are unit vectors
+// in a local frame with the surface normal along +z.
+
+use std::f32::consts::PI;
+
+use glam::{vec3, Vec3};
+use crate::camera::Color;
+
+// Reflectance at normal incidence of dielectrics (ior 1.5)
+const DIELECTRIC_F0: f32 = 0.04;
+
+// Parameters of the model at one surface point.
+#[derive(Clone, Copy, Debug)]
+pub struct Microfacet {
+    pub base_color: Color,
+    // GGX width, the square of the perceptual roughness
+    pub alpha: f32,
+    pub metalness: f32,
+}
+
+impl Microfacet {
+    pub fn new(base_color: Color, roughness: f32, metalness: f32) -> Microfacet {
+        // A perfectly smooth surface is a delta distribution, which can't be evaluated.
+        let roughness = roughness.clamp(0.03, 1.0);
+        Microfacet { base_color, alpha: roughness * roughness, metalness: metalness.clamp(0.0, 1.0) }
+    }
+
+    // BRDF times the cosine of wi.
+    pub fn eval(&self, wo: Vec3, wi: Vec3) -> Color {
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return Color::ZERO;
+        }
+        let h = (wo + wi).normalize();
+        let f0 = Color::splat(DIELECTRIC_F0).lerp(self.base_color, self.metalness);
+        let fresnel = fresnel_schlick(f0, wo.dot(h));
+
+        let specular = fresnel * self.d(h) * self.g2(wo, wi) / (4.0 * wo.z * wi.z);
+        // What the specular layer lets through reaches the diffuse base; metals have none.
+        let transmitted = Color::ONE - fresnel_schlick(f0, wo.z);
+        let diffuse = transmitted * (1.0 - self.metalness) * self.base_color / PI;
+        (specular + diffuse) * wi.z
+    }
+
+    // Chance of sampling the specular lobe rather than the diffuse one.
+    pub fn specular_probability(&self) -> f32 {
+        0.5 + 0.5 * self.metalness
+    }
+
+    // Density of the directions drawn by sample.
+    pub fn pdf(&self, wo: Vec3, wi: Vec3) -> f32 {
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return 0.0;
+        }
+        let h = (wo + wi).normalize();
+        // Density of visible normals, changed from the normal to the reflected direction
+        let specular = self.g1(wo) * self.d(h) / (4.0 * wo.z);
+        let diffuse = wi.z / PI;
+        let p = self.specular_probability();
+        p * specular + (1.0 - p) * diffuse
+    }
+
+    // Draws wi with density pdf from three uniform random numbers: one picks the lobe,
+    // two the direction.
+    pub fn sample(&self, wo: Vec3, lobe: f32, u1: f32, u2: f32) -> Vec3 {
+        if lobe < self.specular_probability() {
+            let h = self.sample_visible_normal(wo, u1, u2);
+            2.0 * wo.dot(h) * h - wo
+        } else {
+            let phi = 2.0 * PI * u1;
+            vec3(phi.cos() * u2.sqrt(), phi.sin() * u2.sqrt(), (1.0 - u2).sqrt())
+        }
+    }
+
+    // Distribution of microfacet normals.
+    fn d(&self, h: Vec3) -> f32 {
+        let a2 = self.alpha * self.alpha;
+        let denominator = h.z * h.z * (a2 - 1.0) + 1.0;
+        a2 / (PI * denominator * denominator)
+    }
+
+    // Smith's auxiliary function for GGX.
+    fn lambda(&self, w: Vec3) -> f32 {
+        let cos2 = w.z * w.z;
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+        0.5 * ((1.0 + self.alpha * self.alpha * tan2).sqrt() - 1.0)
+    }
+
+    fn g1(&self, w: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    fn g2(&self, wo: Vec3, wi: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    // Heitz 2018, "Sampling the GGX Distribution of Visible Normals".
+    fn sample_visible_normal(&self, wo: Vec3, u1: f32, u2: f32) -> Vec3 {
+        let vh = vec3(self.alpha * wo.x, self.alpha * wo.y, wo.z).normalize();
+        let lensq = vh.x * vh.x + vh.y * vh.y;
+        let t1 = if lensq > 0.0 { vec3(-vh.y, vh.x, 0.0) / lensq.sqrt() } else { Vec3::X };
+        let t2 = vh.cross(t1);
+
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+        vec3(self.alpha * nh.x, self.alpha * nh.y, nh.z.max(0.0)).normalize()
+    }
+}
+
+fn fresnel_schlick(f0: Color, cosine: f32) -> Color {
+    f0 + (Color::ONE - f0) * (1.0 - cosine.clamp(0.0, 1.0)).powi(5)
+}
@@ -0,0 +1,91 @@
+use std::f32::consts::PI;
+
+use glam::{vec3, Vec3};
+use rand::prelude::*;
+use crate::hittable::Shape;
+use crate::microfacet::Microfacet;
+use crate::ray::Point;
+
+// Probability densities over directions (per unit solid angle), used to importance sample
+// scattered rays. Every variant can both draw a direction and give the density of any
+// direction, so that they can be mixed.
+pub enum Pdf<'a> {
+    // Proportional to the cosine with the normal, the ideal density for diffuse surfaces.
+    Cosine(Onb),
+    // The same in every direction.
+    Sphere,
+    // Directions from origin towards the shapes, each shape picked equally often.
+    Shapes { shapes: &'a [Shape], origin: Point },
+    // Directions sampled by a microfacet surface lit from the local direction wo.
+    Microfacet { model: Microfacet, onb: Onb, wo: Vec3 },
+    // Picks either density half of the time.
+    Mixture(&'a Pdf<'a>, &'a Pdf<'a>),
+}
+
+impl Pdf<'_> {
+    pub fn value(&self, direction: Vec3) -> f32 {
+        match self {
+            Pdf::Cosine(onb) => (direction.normalize().dot(onb.w) / PI).max(0.0),
+            Pdf::Sphere => 1.0 / (4.0 * PI),
+            Pdf::Shapes { shapes, origin } => {
+                let sum = shapes.iter().map(|shape| shape.pdf_value(*origin, direction)).sum::<f32>();
+                sum / shapes.len() as f32
+            }
+            Pdf::Microfacet { model, onb, wo } => model.pdf(*wo, onb.to_local(direction.normalize())),
+            Pdf::Mixture(a, b) => 0.5 * a.value(direction) + 0.5 * b.value(direction),
+        }
+    }
+
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> Vec3 {
+        match self {
+            Pdf::Cosine(onb) => onb.local(random_cosine_direction(rng)),
+            Pdf::Sphere => random_on_unit_sphere(rng),
+            Pdf::Shapes { shapes, origin } => shapes[rng.gen_range(0..shapes.len())].random_direction(*origin, rng),
+            Pdf::Microfacet { model, onb, wo } => onb.local(model.sample(*wo, rng.gen(), rng.gen(), rng.gen())),
+            Pdf::Mixture(a, b) => if rng.gen::<bool>() { a.generate(rng) } else { b.generate(rng) },
+        }
+    }
+}
+
+// Orthonormal basis with w along a given direction.
+#[derive(Clone, Copy, Debug)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn new(w: Vec3) -> Onb {
+        let w = w.normalize();
+        let a = if w.x.abs() > 0.9 { Vec3::Y } else { Vec3::X };
+        let v = w.cross(a).normalize();
+        let u = w.cross(v);
+        Onb { u, v, w }
+    }
+
+    // The vector with coordinates a in this basis.
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x * self.u + a.y * self.v + a.z * self.w
+    }
+
+    // Coordinates of a in this basis.
+    pub fn to_local(&self, a: Vec3) -> Vec3 {
+        vec3(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+}
+
+// Direction around +z with density cos(theta) / pi.
+fn random_cosine_direction<R: Rng>(rng: &mut R) -> Vec3 {
+    let r1 = rng.gen::<f32>();
+    let r2 = rng.gen::<f32>();
+    let phi = 2.0 * PI * r1;
+    vec3(phi.cos() * r2.sqrt(), phi.sin() * r2.sqrt(), (1.0 - r2).sqrt())
+}
+
+pub(crate) fn random_on_unit_sphere<R: Rng>(rng: &mut R) -> Vec3 {
+    let z = rng.gen_range(-1.0..=1.0f32);
+    let phi = 2.0 * PI * rng.gen::<f32>();
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    vec3(r * phi.cos(), r * phi.sin(), z)
+}
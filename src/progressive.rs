@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use crate::camera::{Camera, Color};
+use crate::hittable::Hittable;
+use crate::image::Image;
+
+// When a progressive render stops. The camera's samples per pixel is the upper limit
+// for every pixel.
+#[derive(Clone, Debug)]
+pub struct Convergence {
+    // Samples added to every unconverged pixel in each pass.
+    pub samples_per_pass: u32,
+    // Samples a pixel takes before its noise estimate is trusted.
+    pub min_samples: u32,
+    // A pixel stops once the standard error of its mean luminance, relative to that mean,
+    // is at most this. None keeps sampling up to the limit.
+    pub target_noise: Option<f32>,
+    // The render stops after the first pass that ends past this budget.
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for Convergence {
+    fn default() -> Convergence {
+        Convergence { samples_per_pass: 4, min_samples: 16, target_noise: Some(0.02), time_budget: None }
+    }
+}
+
+// State after each pass of a progressive render.
+pub struct PassReport<'a> {
+    pub pass: u32,
+    // Mean of the samples taken so far.
+    pub image: &'a Image,
+    pub total_samples: u64,
+    // Pixels that will get more samples in the next pass.
+    pub active_pixels: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone, Copy, Default)]
+struct PixelStats {
+    samples: u32,
+    sum: Color,
+    // For the variance of the luminance
+    luminance_sum: f64,
+    luminance_squared_sum: f64,
+    converged: bool,
+}
+
+impl PixelStats {
+    fn add(&mut self, color: Color) {
+        let luminance = luminance(color) as f64;
+        self.samples += 1;
+        self.sum += color;
+        self.luminance_sum += luminance;
+        self.luminance_squared_sum += luminance * luminance;
+    }
+
+    fn mean(&self) -> Color {
+        if self.samples == 0 { Color::ZERO } else { self.sum / self.samples as f32 }
+    }
+
+    // Standard error of the mean luminance relative to the mean. The small offset keeps
+    // nearly black pixels from needing an unbounded number of samples.
+    fn relative_noise(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.samples as f64;
+        let mean = self.luminance_sum / n;
+        let variance = ((self.luminance_squared_sum - n * mean * mean) / (n - 1.0)).max(0.0);
+        (variance / n).sqrt() / (mean.abs() + 1e-3)
+    }
+}
+
+fn luminance(color: Color) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+impl Camera {
+    // Renders in passes until every pixel has converged, reached the camera's samples per
+    // pixel, or the time budget runs out. on_pass sees the image after every pass.
+    pub fn render_progressive<W, F>(&self, world: &W, convergence: &Convergence, mut on_pass: F) -> Image
+    where
+        W: Hittable + Sync,
+        F: FnMut(&PassReport),
+    {
+        assert!(convergence.samples_per_pass > 0, "A pass needs at least one sample.");
+        let start = Instant::now();
+        let width = self.image_width;
+        let mut stats = vec![PixelStats::default(); width as usize * self.image_height as usize];
+        let mut total_samples = 0;
+
+        let mut pass = 0;
+        loop {
+            let remaining = self.samples_per_pixel;
+            let samples_taken = stats.par_iter_mut()
+                .enumerate()
+                .filter(|(_, pixel)| !pixel.converged)
+                .map(|(index, pixel)| {
+                    let (i, j) = ((index % width as usize) as u32, (index / width as usize) as u32);
+                    let mut rng = self.pixel_rng(i, j, pass);
+                    let samples = convergence.samples_per_pass.min(remaining - pixel.samples);
+                    for _ in 0..samples {
+                        pixel.add(self.sample_pixel(i, j, world, &mut rng));
+                    }
+
+                    let noise_reached = convergence.target_noise.is_some_and(|target| {
+                        pixel.samples >= convergence.min_samples && pixel.relative_noise() <= target as f64
+                    });
+                    pixel.converged = noise_reached || pixel.samples >= self.samples_per_pixel;
+                    samples as u64
+                })
+                .sum::<u64>();
+            total_samples += samples_taken;
+
+            let image = Image::from_pixels(width, self.image_height, stats.iter().map(PixelStats::mean).collect());
+            let active_pixels = stats.iter().filter(|pixel| !pixel.converged).count();
+            let elapsed = start.elapsed();
+            on_pass(&PassReport { pass, image: &image, total_samples, active_pixels, elapsed });
+
+            let out_of_time = convergence.time_budget.is_some_and(|budget| elapsed >= budget);
+            if active_pixels == 0 || out_of_time {
+                return image;
+            }
+            pass += 1;
+        }
+    }
+}
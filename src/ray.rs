@@ -1,9 +1,14 @@
 use glam::Vec3;
 
 pub type Point = Vec3;
+#[derive(Clone, Copy, Debug)]
 pub struct Ray {
     pub dir: Vec3,
     pub orig: Point,
+    // Moment within the camera's shutter interval the ray samples, for moving objects.
+    pub time: f32,
+    // Wavelength in nm the path follows in spectral mode, None for RGB rendering.
+    pub wavelength: Option<f32>,
 }
 
 impl Ray {
@@ -12,6 +17,15 @@ impl Ray {
     }
 
     pub fn new(orig: Point, dir: Vec3) -> Ray {
-        Ray {orig, dir}
+        Ray {orig, dir, time: 0.0, wavelength: None}
     }
-}
\ No newline at end of file
+
+    pub fn with_time(orig: Point, dir: Vec3, time: f32) -> Ray {
+        Ray {orig, dir, time, wavelength: None}
+    }
+
+    // Next ray of the same path, at the same time and wavelength.
+    pub fn scattered(&self, orig: Point, dir: Vec3) -> Ray {
+        Ray {orig, dir, ..*self}
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::tiles::Checkpoint;
+
+pub type ProgressCallback<'a> = Box<dyn Fn(&RenderProgress) + Sync + 'a>;
+
+// Settings of Camera::render_with. The default renders without reporting anything.
+#[derive(Default)]
+pub struct RenderOptions<'a> {
+    // Called once before the first tile and after every finished tile.
+    pub on_progress: Option<ProgressCallback<'a>>,
+    // Checked before every row of a tile. A cancelled render still saves its checkpoint.
+    pub cancel: Option<CancelToken>,
+    pub checkpoint: Option<Checkpoint>,
+}
+
+// How far a render has come. Tiles restored from a checkpoint count as done.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderProgress {
+    pub tiles_done: usize,
+    pub tiles_total: usize,
+    pub pixels_done: u64,
+    pub pixels_total: u64,
+    pub elapsed: Duration,
+    // Estimated time left, from the speed of the tiles rendered so far.
+    pub eta: Option<Duration>,
+}
+
+// Shared flag to stop a running render from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+pub enum RenderError {
+    Cancelled,
+    // Reading or writing the checkpoint failed
+    Io(io::Error),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Cancelled => write!(f, "render cancelled"),
+            RenderError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<io::Error> for RenderError {
+    fn from(error: io::Error) -> RenderError {
+        RenderError::Io(error)
+    }
+}
+
+// Progress callback that draws an indicatif progress bar on stderr.
+#[cfg(feature = "progress-bar")]
+pub fn progress_bar<'a>() -> ProgressCallback<'a> {
+    let bar = indicatif::ProgressBar::new(0);
+    Box::new(move |progress| {
+        bar.set_length(progress.pixels_total);
+        bar.set_position(progress.pixels_done);
+        if progress.tiles_done == progress.tiles_total {
+            bar.finish();
+        }
+    })
+}
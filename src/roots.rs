@@ -0,0 +1,141 @@
+// Real roots of polynomials up to degree four, for intersecting rays with quadric and quartic
+// surfaces. The closed forms follow Jochen Schwarze, "Cubic and Quartic Roots" (Graphics
+// Gems, 1990). They work in f64, as the quartic loses too much precision in f32, and every
+// root is refined with Newton's method on the original polynomial.
+//
+// Coefficients are given from the highest power down, and roots are returned in ascending
+// order. Double roots may be returned once or twice.
+
+use std::f64::consts::PI;
+
+const EPSILON: f64 = 1e-9;
+const NEWTON_STEPS: usize = 2;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+// Roots of a x^2 + b x + c.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return if b == 0.0 { Vec::new() } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    // Avoids the cancellation of -b + sqrt(discriminant) when b is large.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = if q == 0.0 { vec![0.0] } else { vec![q / a, c / q] };
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// Roots of a x^3 + b x^2 + c x + d.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return solve_quadratic(b, c, d);
+    }
+    let roots = normal_cubic(b / a, c / a, d / a);
+    polish(roots, &[a, b, c, d])
+}
+
+// Roots of a x^4 + b x^3 + c x^2 + d x + e.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return solve_cubic(b, c, d, e);
+    }
+    let roots = normal_quartic(b / a, c / a, d / a, e / a);
+    polish(roots, &[a, b, c, d, e])
+}
+
+// Roots of x^3 + a x^2 + b x + c.
+fn normal_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substituting x = y - a/3 gives y^3 + 3p y + 2q.
+    let p = (b - a * a / 3.0) / 3.0;
+    let q = (2.0 / 27.0 * a * a * a - a * b / 3.0 + c) / 2.0;
+    let p_cubed = p * p * p;
+    let discriminant = q * q + p_cubed;
+
+    let roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots
+        let phi = (-q / (-p_cubed).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![t * phi.cos(), -t * (phi + PI / 3.0).cos(), -t * (phi - PI / 3.0).cos()]
+    } else {
+        let sqrt_discriminant = discriminant.sqrt();
+        vec![(sqrt_discriminant - q).cbrt() - (sqrt_discriminant + q).cbrt()]
+    };
+    roots.into_iter().map(|y| y - a / 3.0).collect()
+}
+
+// Roots of x^4 + a x^3 + b x^2 + c x + d.
+fn normal_quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // Substituting x = y - a/4 gives y^4 + p y^2 + q y + r.
+    let a2 = a * a;
+    let p = -3.0 / 8.0 * a2 + b;
+    let q = a2 * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * a2 * a2 + a2 * b / 16.0 - a * c / 4.0 + d;
+
+    let roots = if is_zero(r) {
+        // y (y^3 + p y + q) = 0
+        let mut roots = normal_cubic(0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // Any real root z of the resolvent cubic splits the quartic into two quadratics.
+        let z = normal_cubic(-p / 2.0, -r, r * p / 2.0 - q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let (Some(u), Some(v)) = (non_negative_sqrt(z * z - r), non_negative_sqrt(2.0 * z - p)) else {
+            return Vec::new();
+        };
+        let v = if q < 0.0 { -v } else { v };
+        let mut roots = solve_quadratic(1.0, v, z - u);
+        roots.extend(solve_quadratic(1.0, -v, z + u));
+        roots
+    };
+    roots.into_iter().map(|y| y - a / 4.0).collect()
+}
+
+fn non_negative_sqrt(x: f64) -> Option<f64> {
+    if is_zero(x) {
+        Some(0.0)
+    } else if x > 0.0 {
+        Some(x.sqrt())
+    } else {
+        None
+    }
+}
+
+fn polish(roots: Vec<f64>, coefficients: &[f64]) -> Vec<f64> {
+    let mut roots = roots.into_iter()
+        .map(|mut x| {
+            for _ in 0..NEWTON_STEPS {
+                let (value, derivative) = evaluate(coefficients, x);
+                let next = x - value / derivative;
+                // Near double roots the step can overshoot; keep only improvements.
+                let improved = evaluate(coefficients, next).0.abs() < value.abs();
+                if !improved {
+                    break;
+                }
+                x = next;
+            }
+            x
+        })
+        .collect::<Vec<f64>>();
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+// Value and derivative of the polynomial at x, by Horner's scheme.
+fn evaluate(coefficients: &[f64], x: f64) -> (f64, f64) {
+    coefficients.iter().fold((0.0, 0.0), |(value, derivative), &c| (value * x + c, derivative * x + value))
+}
@@ -0,0 +1,621 @@
+// Text scene descriptions.
+//
+// A scene file has one statement per line; '#' starts a comment. Every statement is a
+// keyword, an optional name, and key=value parameters. Vectors and colors are written
+// as x,y,z without spaces, and numbers may be fractions like 16/9.
+//
+//     camera width=400 aspect=16/9 samples=64 depth=50 vfov=20 from=13,2,3 at=0,0,0
+//     background sky
+//     texture checks checker scale=0.32 even=0.2,0.3,0.1 odd=0.9,0.9,0.9
+//     material ground lambertian texture=checks
+//     material glass dielectric ior=1.5
+//     material bottle dielectric ior=1.5 tint=0.6,0.9,0.7 distance=0.5
+//     material prism dielectric cauchy=1.5,0.02
+//     material gold pbr albedo=1,0.78,0.34 roughness=0.3 metalness=1
+//     sphere center=0,1,0 radius=1 material=glass
+//
+// Besides spheres, quads, triangles and boxes there are infinite planes and capped
+// cylinders, cones and tori:
+//
+//     plane point=0,0,0 normal=0,1,0 material=ground
+//     disk center=0,1,0 normal=0,1,0 radius=1 material=white
+//     cylinder base=0,0,0 top=0,2,0 radius=0.5 material=white
+//     cone base=0,0,0 apex=0,2,0 radius=0.5 material=white
+//     torus center=0,1,0 normal=0,1,0 major=1 minor=0.25 material=white
+//
+// A moving_sphere goes from 'from' at time 0 to 'to' at time 1; it is blurred over the
+// camera's shutter=open,close interval.
+//
+// A medium fills a sphere or box with smoke of the given density:
+//
+//     medium box min=0,0,0 max=1,1,1 density=0.01 albedo=1,1,1
+//
+// Dielectrics take their index of refraction as ior=n (or ior=bk7, ior=diamond),
+// cauchy=a,b or sellmeier=b1,b2,b3,c1,c2,c3 (micrometers). The last two only disperse
+// light with the camera's spectral=true.
+//
+// Every shape statement can be placed with optional scale=s (or x,y,z), rotate=x,y,z
+// (degrees about the x, then y, then z axis) and translate=x,y,z, applied in that order.
+//
+// Shapes with a name=NAME are kept aside instead of being added to the scene. Two of them
+// combine into a solid with union, intersection or difference (the first without the
+// second), which can be named again in turn:
+//
+//     sphere center=-0.4,1,0 radius=1 material=glass name=left
+//     sphere center=0.4,1,0 radius=1 material=glass name=right
+//     intersection a=left b=right
+//
+// Relative file paths (meshes, images) are resolved against the scene file's directory.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use glam::{Affine3A, EulerRot, Quat, Vec3};
+use rand::prelude::*;
+use crate::background::Background;
+use crate::camera::{Camera, CameraBuilder, Color};
+use crate::csg::CsgOperation;
+use crate::hittable::Shape;
+use crate::image_io;
+use crate::material::{Ior, Material};
+use crate::mesh::Mesh;
+use crate::texture::Texture;
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    // line is 1-based
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "{}", error),
+            SceneError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(error: io::Error) -> SceneError {
+        SceneError::Io(error)
+    }
+}
+
+// Reads and parses a scene file.
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<(Camera, Vec<Shape>), SceneError> {
+    let (camera, world) = read_scene(path)?;
+    Ok((camera.build(), world))
+}
+
+// Like load_scene, but leaves the camera as a builder.
+pub fn read_scene<P: AsRef<Path>>(path: P) -> Result<(CameraBuilder, Vec<Shape>), SceneError> {
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.as_ref().display(), e)))?;
+    let base_dir = path.as_ref().parent().unwrap_or(Path::new("")).to_path_buf();
+    parse_scene(&source, &base_dir)
+}
+
+// Parses scene source. The camera is returned as a builder so callers can still override
+// settings such as the image size.
+pub fn parse_scene(source: &str, base_dir: &Path) -> Result<(CameraBuilder, Vec<Shape>), SceneError> {
+    let mut parser = Parser {
+        base_dir: base_dir.to_path_buf(),
+        camera: CameraBuilder::default(),
+        textures: HashMap::new(),
+        materials: HashMap::new(),
+        shapes: HashMap::new(),
+        world: Vec::new(),
+    };
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let content = line.split('#').next().unwrap_or("");
+        if content.trim().is_empty() {
+            continue;
+        }
+        parser.statement(content)
+            .map_err(|message| SceneError::Parse { line: line_number, message })?;
+    }
+    // Sample the emitters directly. Those inside instances, media and meshes are left out.
+    let lights = parser.world.iter()
+        .filter(|shape| match shape {
+            Shape::Sphere { material, .. } | Shape::Quad { material, .. } | Shape::Triangle { material, .. } =>
+                matches!(material, Material::DiffuseLight(_)),
+            _ => false,
+        })
+        .cloned()
+        .collect();
+    parser.camera.set_lights(lights);
+    Ok((parser.camera, parser.world))
+}
+
+struct Parser {
+    base_dir: PathBuf,
+    camera: CameraBuilder,
+    textures: HashMap<String, Texture>,
+    materials: HashMap<String, Material>,
+    // Shapes given a name=, kept out of the world for solid operations
+    shapes: HashMap<String, Vec<Shape>>,
+    world: Vec<Shape>,
+}
+
+impl Parser {
+    fn statement(&mut self, content: &str) -> Result<(), String> {
+        let mut statement = Statement::parse(content)?;
+        let first_new_shape = self.world.len();
+        match statement.keyword {
+            "camera" => self.camera(&mut statement)?,
+            "background" => {
+                let background = self.background(&mut statement)?;
+                self.camera.set_background(background);
+            }
+            "texture" => {
+                let name = statement.name()?;
+                let texture = self.texture(&mut statement)?;
+                self.textures.insert(name, texture);
+            }
+            "material" => {
+                let name = statement.name()?;
+                let material = self.material(&mut statement)?;
+                self.materials.insert(name, material);
+            }
+            "sphere" => {
+                let center = statement.vec3("center")?;
+                let radius = statement.number("radius")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_sphere(center, radius, material));
+            }
+            "moving_sphere" => {
+                let center0 = statement.vec3("from")?;
+                let center1 = statement.vec3("to")?;
+                let radius = statement.number("radius")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_moving_sphere(center0, center1, radius, material));
+            }
+            "quad" => {
+                let corner = statement.vec3("corner")?;
+                let u = statement.vec3("u")?;
+                let v = statement.vec3("v")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_quad(corner, u, v, material));
+            }
+            "box" => {
+                let min = statement.vec3("min")?;
+                let max = statement.vec3("max")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.extend(Shape::new_box(min, max, material));
+            }
+            "triangle" => {
+                let a = statement.vec3("a")?;
+                let b = statement.vec3("b")?;
+                let c = statement.vec3("c")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_triangle(a, b, c, material));
+            }
+            "plane" => {
+                let point = statement.vec3("point")?;
+                let normal = statement.vec3("normal")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_plane(point, normal, material));
+            }
+            "disk" => {
+                let center = statement.vec3("center")?;
+                let normal = statement.vec3("normal")?;
+                let radius = statement.number("radius")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_disk(center, normal, radius, material));
+            }
+            "cylinder" => {
+                let base = statement.vec3("base")?;
+                let top = statement.vec3("top")?;
+                let radius = statement.number("radius")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_cylinder(base, top, radius, material));
+            }
+            "cone" => {
+                let base = statement.vec3("base")?;
+                let apex = statement.vec3("apex")?;
+                let radius = statement.number("radius")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_cone(base, apex, radius, material));
+            }
+            "torus" => {
+                let center = statement.vec3("center")?;
+                let normal = statement.vec3("normal")?;
+                let major = statement.number("major")?;
+                let minor = statement.number("minor")?;
+                let material = self.material_ref(&mut statement)?;
+                self.world.push(Shape::new_torus(center, normal, major, minor, material));
+            }
+            "medium" => {
+                let boundary = self.medium_boundary(&mut statement)?;
+                let density = statement.number("density")?;
+                let albedo = statement.vec3("albedo")?;
+                self.world.push(Shape::new_constant_medium(boundary, density, albedo.into()));
+            }
+            "mesh" => {
+                let file = self.path(statement.text("file")?);
+                let material = self.material_ref(&mut statement)?;
+                let mesh = Mesh::load_obj(&file, material).map_err(|e| e.to_string())?;
+                self.world.push(Shape::new_mesh(mesh));
+            }
+            "union" | "intersection" | "difference" => {
+                let operation = match statement.keyword {
+                    "union" => CsgOperation::Union,
+                    "intersection" => CsgOperation::Intersection,
+                    _ => CsgOperation::Difference,
+                };
+                let a = self.shapes_ref(&mut statement, "a")?;
+                let b = self.shapes_ref(&mut statement, "b")?;
+                self.world.push(Shape::new_csg(operation, a, b));
+            }
+            keyword => return Err(format!("unknown statement '{}'", keyword)),
+        }
+        if self.world.len() > first_new_shape {
+            if let Some(transform) = transform(&mut statement)? {
+                let shapes = self.world.split_off(first_new_shape);
+                self.world.push(Shape::new_instance(shapes, transform));
+            }
+            if let Some(name) = statement.optional("name", |s| Ok(s.to_string()))? {
+                let shapes = self.world.split_off(first_new_shape);
+                self.shapes.insert(name, shapes);
+            }
+        }
+        statement.finish()
+    }
+
+    fn camera(&mut self, statement: &mut Statement) -> Result<(), String> {
+        let camera = &mut self.camera;
+        if let Some(width) = statement.optional("width", parse_positive_u32)? {
+            camera.set_image_width(width);
+        }
+        if let Some(aspect) = statement.optional("aspect", parse_positive)? {
+            camera.set_aspect_ratio(aspect);
+        }
+        if let Some(samples) = statement.optional("samples", parse_positive_u32)? {
+            camera.set_samples_per_pixel(samples);
+        }
+        if let Some(depth) = statement.optional("depth", parse_u32)? {
+            camera.set_max_depth(depth);
+        }
+        if let Some(vfov) = statement.optional("vfov", parse_field_of_view)? {
+            camera.set_vfov(vfov);
+        }
+        let from = statement.optional("from", parse_vec3)?;
+        let at = statement.optional("at", parse_vec3)?;
+        match (from, at) {
+            (Some(from), Some(at)) => {
+                // The view direction and up direction span the image plane
+                let direction = at - from;
+                if direction == Vec3::ZERO {
+                    return Err("camera 'from' and 'at' must be different points".to_string());
+                }
+                if direction.normalize().cross(camera.vup()).length() < 1e-6 {
+                    return Err("camera cannot look straight up or down".to_string());
+                }
+                camera.set_view_direction(from, at);
+            }
+            (None, None) => {}
+            _ => return Err("camera needs both 'from' and 'at'".to_string()),
+        }
+        let defocus = statement.optional("defocus", parse_number)?;
+        let focus = statement.optional("focus", parse_positive)?;
+        match (defocus, focus) {
+            (Some(defocus), Some(focus)) => { camera.set_focus(defocus, focus); }
+            (None, None) => {}
+            _ => return Err("camera needs both 'defocus' and 'focus'".to_string()),
+        }
+        if let Some((open, close)) = statement.optional("shutter", parse_interval)? {
+            camera.set_shutter(open, close);
+        }
+        if let Some(spectral) = statement.optional("spectral", parse_bool)? {
+            camera.set_spectral(spectral);
+        }
+        Ok(())
+    }
+
+    fn medium_boundary(&mut self, statement: &mut Statement) -> Result<Vec<Shape>, String> {
+        // Only the shape of the boundary matters, not its material
+        let material = Material::Lambertian(Color::ZERO.into());
+        let kind = statement.positional()?;
+        match kind.as_str() {
+            "sphere" => Ok(vec![Shape::new_sphere(statement.vec3("center")?, statement.number("radius")?, &material)]),
+            "box" => Ok(Shape::new_box(statement.vec3("min")?, statement.vec3("max")?, &material)),
+            other => Err(format!("unknown medium boundary '{}'", other)),
+        }
+    }
+
+    fn background(&mut self, statement: &mut Statement) -> Result<Background, String> {
+        let kind = statement.positional()?;
+        match kind.as_str() {
+            "sky" => Ok(Background::sky()),
+            "solid" => Ok(Background::Solid(statement.vec3("color")?)),
+            "gradient" => Ok(Background::Gradient { bottom: statement.vec3("bottom")?, top: statement.vec3("top")? }),
+            "map" => {
+                let file = self.path(statement.text("file")?);
+                let image = image_io::load(&file).map_err(|e| e.to_string())?;
+                let rotation = statement.optional("rotation", parse_number)?.unwrap_or(0.0);
+                let intensity = statement.optional("intensity", parse_number)?.unwrap_or(1.0);
+                Ok(Background::EnvironmentMap { image: Arc::new(image), rotation, intensity })
+            }
+            kind => Err(format!("unknown background '{}' (expected sky, solid, gradient or map)", kind)),
+        }
+    }
+
+    fn texture(&mut self, statement: &mut Statement) -> Result<Texture, String> {
+        let kind = statement.positional()?;
+        match kind.as_str() {
+            "solid" => Ok(Texture::Solid(statement.vec3("color")?)),
+            "checker" => {
+                let scale = statement.number("scale")?;
+                let even = self.texture_or_color(statement, "even")?;
+                let odd = self.texture_or_color(statement, "odd")?;
+                Ok(Texture::checker(scale, even, odd))
+            }
+            "image" => Texture::image(self.path(statement.text("file")?)).map_err(|e| e.to_string()),
+            "noise" | "marble" => {
+                let scale = statement.number("scale")?;
+                let seed = statement.optional("seed", |s| s.parse::<u64>().map_err(|_| format!("invalid seed '{}'", s)))?;
+                let mut rng = StdRng::seed_from_u64(seed.unwrap_or(0));
+                Ok(if kind == "noise" { Texture::noise(scale, &mut rng) } else { Texture::marble(scale, &mut rng) })
+            }
+            kind => Err(format!("unknown texture '{}' (expected solid, checker, image, noise or marble)", kind)),
+        }
+    }
+
+    // A parameter that is either a color or the name of a texture.
+    fn texture_or_color(&self, statement: &mut Statement, key: &str) -> Result<Texture, String> {
+        let value = statement.text(key)?;
+        if let Some(texture) = self.textures.get(&value) {
+            return Ok(texture.clone());
+        }
+        parse_vec3(&value)
+            .map(Texture::Solid)
+            .map_err(|_| format!("'{}' is neither a color nor a known texture", value))
+    }
+
+    fn material(&mut self, statement: &mut Statement) -> Result<Material, String> {
+        let kind = statement.positional()?;
+        match kind.as_str() {
+            "lambertian" => Ok(Material::Lambertian(self.albedo(statement)?)),
+            "metal" => {
+                let albedo = self.albedo(statement)?;
+                let fuzz = statement.optional("fuzz", parse_number)?.unwrap_or(0.0);
+                Ok(Material::Metal(albedo, fuzz))
+            }
+            "dielectric" => {
+                let ior = ior(statement)?;
+                // Tinted glass lets through the tint after 'distance' (default 1) in it
+                match statement.optional("tint", parse_vec3)? {
+                    Some(tint) => {
+                        let distance = statement.optional("distance", parse_number)?.unwrap_or(1.0);
+                        Ok(Material::tinted_glass(ior, tint, distance))
+                    }
+                    None => Ok(Material::Dielectric(ior)),
+                }
+            }
+            "light" => Ok(Material::DiffuseLight(statement.vec3("emit")?)),
+            "pbr" => {
+                let base_color = self.albedo(statement)?;
+                let roughness = statement.number("roughness")?;
+                let metalness = statement.optional("metalness", parse_number)?.unwrap_or(0.0);
+                Ok(Material::Microfacet(base_color, roughness, metalness))
+            }
+            kind => Err(format!("unknown material '{}' (expected lambertian, metal, dielectric, pbr or light)", kind)),
+        }
+    }
+
+    // Either albedo=r,g,b or texture=name.
+    fn albedo(&self, statement: &mut Statement) -> Result<Texture, String> {
+        match (statement.optional("albedo", parse_vec3)?, statement.optional("texture", |s| Ok(s.to_string()))?) {
+            (Some(color), None) => Ok(Texture::Solid(color)),
+            (None, Some(name)) => self.textures.get(&name)
+                .cloned()
+                .ok_or_else(|| format!("unknown texture '{}'", name)),
+            _ => Err("expected either 'albedo' or 'texture'".to_string()),
+        }
+    }
+
+    fn material_ref(&self, statement: &mut Statement) -> Result<&Material, String> {
+        let name = statement.text("material")?;
+        self.materials.get(&name).ok_or_else(|| format!("unknown material '{}'", name))
+    }
+
+    fn shapes_ref(&self, statement: &mut Statement, key: &str) -> Result<Vec<Shape>, String> {
+        let name = statement.text(key)?;
+        self.shapes.get(&name).cloned().ok_or_else(|| format!("unknown shape '{}'", name))
+    }
+
+    fn path(&self, file: String) -> PathBuf {
+        self.base_dir.join(file)
+    }
+}
+
+// One scene line, split into keyword, positional words and key=value parameters.
+struct Statement<'a> {
+    keyword: &'a str,
+    positional: Vec<&'a str>,
+    params: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Statement<'a> {
+    fn parse(content: &'a str) -> Result<Statement<'a>, String> {
+        let mut tokens = content.split_whitespace();
+        let keyword = tokens.next().ok_or("empty statement")?;
+        let mut positional = Vec::new();
+        let mut params: Vec<(&str, &str)> = Vec::new();
+        for token in tokens {
+            match token.split_once('=') {
+                Some((key, value)) => {
+                    if params.iter().any(|(k, _)| *k == key) {
+                        return Err(format!("'{}' is given more than once", key));
+                    }
+                    params.push((key, value));
+                }
+                None if params.is_empty() => positional.push(token),
+                None => return Err(format!("unexpected '{}' after parameters", token)),
+            }
+        }
+        positional.reverse();
+        Ok(Statement { keyword, positional, params })
+    }
+
+    fn positional(&mut self) -> Result<String, String> {
+        self.positional.pop()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} is missing its type", self.keyword))
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        self.positional.pop()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} is missing its name", self.keyword))
+    }
+
+    fn optional<T>(&mut self, key: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Option<T>, String> {
+        match self.params.iter().position(|(k, _)| *k == key) {
+            Some(index) => {
+                let (_, value) = self.params.remove(index);
+                parse(value).map(Some).map_err(|e| format!("{}: {}", key, e))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn required<T>(&mut self, key: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<T, String> {
+        self.optional(key, parse)?
+            .ok_or_else(|| format!("{} is missing '{}'", self.keyword, key))
+    }
+
+    fn number(&mut self, key: &str) -> Result<f32, String> {
+        self.required(key, parse_number)
+    }
+
+    fn vec3(&mut self, key: &str) -> Result<Vec3, String> {
+        self.required(key, parse_vec3)
+    }
+
+    fn text(&mut self, key: &str) -> Result<String, String> {
+        self.required(key, |s| Ok(s.to_string()))
+    }
+
+    // Fails on anything the statement did not use.
+    fn finish(&self) -> Result<(), String> {
+        if let Some(word) = self.positional.last() {
+            return Err(format!("unexpected '{}'", word));
+        }
+        match self.params.first() {
+            Some((key, _)) => Err(format!("unknown parameter '{}' for {}", key, self.keyword)),
+            None => Ok(()),
+        }
+    }
+}
+
+// Index of refraction: ior=n (or bk7, diamond), cauchy=a,b or sellmeier=b1,b2,b3,c1,c2,c3.
+fn ior(statement: &mut Statement) -> Result<Ior, String> {
+    let constant = statement.optional("ior", |text| match text {
+        "bk7" => Ok(Ior::BK7),
+        "diamond" => Ok(Ior::DIAMOND),
+        _ => parse_number(text).map(Ior::Constant),
+    })?;
+    let cauchy = statement.optional("cauchy", |text| match parse_numbers(text)?.as_slice() {
+        [a, b] => Ok(Ior::Cauchy { a: *a, b: *b }),
+        _ => Err(format!("expected a,b but found '{}'", text)),
+    })?;
+    let sellmeier = statement.optional("sellmeier", |text| match parse_numbers(text)?.as_slice() {
+        [b1, b2, b3, c1, c2, c3] => Ok(Ior::Sellmeier { b: [*b1, *b2, *b3], c: [*c1, *c2, *c3] }),
+        _ => Err(format!("expected b1,b2,b3,c1,c2,c3 but found '{}'", text)),
+    })?;
+    match (constant, cauchy, sellmeier) {
+        (Some(ior), None, None) | (None, Some(ior), None) | (None, None, Some(ior)) => Ok(ior),
+        _ => Err("expected one of 'ior', 'cauchy' or 'sellmeier'".to_string()),
+    }
+}
+
+// Optional placement of a shape statement: scaled, rotated about x, then y, then z,
+// and then translated.
+fn transform(statement: &mut Statement) -> Result<Option<Affine3A>, String> {
+    let scale = statement.optional("scale", parse_scale)?;
+    let rotate = statement.optional("rotate", parse_vec3)?;
+    let translate = statement.optional("translate", parse_vec3)?;
+    if scale.is_none() && rotate.is_none() && translate.is_none() {
+        return Ok(None);
+    }
+    let rotate = rotate.unwrap_or(Vec3::ZERO);
+    let rotation = Quat::from_euler(EulerRot::ZYX, rotate.z.to_radians(), rotate.y.to_radians(), rotate.x.to_radians());
+    Ok(Some(Affine3A::from_scale_rotation_translation(
+        scale.unwrap_or(Vec3::ONE),
+        rotation,
+        translate.unwrap_or(Vec3::ZERO))))
+}
+
+// One number for uniform scaling, or x,y,z.
+fn parse_scale(text: &str) -> Result<Vec3, String> {
+    if text.contains(',') { parse_vec3(text) } else { parse_number(text).map(Vec3::splat) }
+}
+
+fn parse_number(text: &str) -> Result<f32, String> {
+    let invalid = || format!("invalid number '{}'", text);
+    match text.split_once('/') {
+        Some((numerator, denominator)) => {
+            let n: f32 = numerator.parse().map_err(|_| invalid())?;
+            let d: f32 = denominator.parse().map_err(|_| invalid())?;
+            Ok(n / d)
+        }
+        None => text.parse().map_err(|_| invalid()),
+    }
+}
+
+fn parse_u32(text: &str) -> Result<u32, String> {
+    text.parse().map_err(|_| format!("invalid whole number '{}'", text))
+}
+
+fn parse_positive_u32(text: &str) -> Result<u32, String> {
+    match parse_u32(text)? {
+        0 => Err(format!("expected a positive whole number but found '{}'", text)),
+        n => Ok(n),
+    }
+}
+
+fn parse_positive(text: &str) -> Result<f32, String> {
+    match parse_number(text)? {
+        x if x > 0.0 && x.is_finite() => Ok(x),
+        _ => Err(format!("expected a positive number but found '{}'", text)),
+    }
+}
+
+// Vertical field of view in degrees, strictly between 0 and 180.
+fn parse_field_of_view(text: &str) -> Result<f32, String> {
+    match parse_number(text)? {
+        degrees if degrees > 0.0 && degrees < 180.0 => Ok(degrees),
+        _ => Err(format!("expected an angle between 0 and 180 degrees but found '{}'", text)),
+    }
+}
+
+fn parse_bool(text: &str) -> Result<bool, String> {
+    text.parse().map_err(|_| format!("expected true or false but found '{}'", text))
+}
+
+fn parse_numbers(text: &str) -> Result<Vec<f32>, String> {
+    text.split(',').map(parse_number).collect()
+}
+
+fn parse_interval(text: &str) -> Result<(f32, f32), String> {
+    match parse_numbers(text)?.as_slice() {
+        [min, max] if min <= max => Ok((*min, *max)),
+        _ => Err(format!("expected min,max but found '{}'", text)),
+    }
+}
+
+fn parse_vec3(text: &str) -> Result<Vec3, String> {
+    match parse_numbers(text)?.as_slice() {
+        [x, y, z] => Ok(Vec3::new(*x, *y, *z)),
+        _ => Err(format!("expected x,y,z but found '{}'", text)),
+    }
+}
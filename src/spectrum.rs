@@ -0,0 +1,90 @@
+// Conversion between RGB and spectra for spectral rendering.
+//
+// In spectral mode every camera path follows one wavelength, drawn uniformly from the
+// visible range. Colors along the path stay RGB, and only the color the path carries in the
+// end is read as a spectrum: one that is constant over three bands (blue, green, red), with
+// the band values chosen so that the spectrum converts back to exactly that color. The band
+// values mix the RGB channels, so the spectrum of a product of colors is not the product of
+// their spectra, and multiplying along the path in RGB only approximates multiplying the
+// spectra. Only the path itself depends on the wavelength, e.g. through dispersion.
+
+use std::sync::OnceLock;
+
+use glam::{Mat3, Vec3};
+use rand::Rng;
+use crate::camera::Color;
+
+pub const MIN_WAVELENGTH: f32 = 380.0;
+pub const MAX_WAVELENGTH: f32 = 780.0;
+
+// Wavelengths (nm) where the bands of the RGB spectra meet.
+const BAND_EDGES: [f32; 2] = [490.0, 580.0];
+
+// Step of the numerical integrals over the visible range, in nm.
+const STEP: f32 = 0.5;
+
+pub(crate) fn sample_wavelength<R: Rng>(rng: &mut R) -> f32 {
+    rng.gen_range(MIN_WAVELENGTH..MAX_WAVELENGTH)
+}
+
+// CIE 1931 2° color matching functions at the wavelength (nm), using the multi-lobe fit of
+// Wyman, Sloan and Shirley (2013).
+pub fn cie_xyz(wavelength: f32) -> Vec3 {
+    let g = |mu: f32, sigma_below: f32, sigma_above: f32| {
+        let sigma = if wavelength < mu { sigma_below } else { sigma_above };
+        let x = (wavelength - mu) / sigma;
+        (-0.5 * x * x).exp()
+    };
+    Vec3::new(
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    )
+}
+
+// CIE XYZ to linear sRGB (D65).
+pub fn xyz_to_rgb(xyz: Vec3) -> Color {
+    let m = Mat3::from_cols(
+        Vec3::new(3.240_454_2, -0.969_266, 0.055_643_4),
+        Vec3::new(-1.537_138_5, 1.876_010_8, -0.204_025_9),
+        Vec3::new(-0.498_531_4, 0.041_556, 1.057_225_2),
+    );
+    m * xyz
+}
+
+// Contribution of a single wavelength sample to the RGB estimate, for a path that carried
+// color at that wavelength. Averaged over uniformly drawn wavelengths it gives back color.
+pub fn rgb_at_wavelength(color: Color, wavelength: f32) -> Color {
+    let basis = basis();
+    let band_values = basis.rgb_to_bands * color;
+    let value = band_values[band(wavelength)];
+    xyz_to_rgb(cie_xyz(wavelength)) * value * (MAX_WAVELENGTH - MIN_WAVELENGTH) / basis.y_integral
+}
+
+fn band(wavelength: f32) -> usize {
+    BAND_EDGES.iter().filter(|&&edge| wavelength >= edge).count()
+}
+
+struct Basis {
+    // Band values of the spectrum of an RGB color
+    rgb_to_bands: Mat3,
+    // Integral of the luminance matching function, which spectra are normalized by
+    y_integral: f32,
+}
+
+fn basis() -> &'static Basis {
+    static BASIS: OnceLock<Basis> = OnceLock::new();
+    BASIS.get_or_init(|| {
+        let steps = ((MAX_WAVELENGTH - MIN_WAVELENGTH) / STEP) as usize;
+        let wavelengths = (0..steps).map(|i| MIN_WAVELENGTH + (i as f32 + 0.5) * STEP);
+        let y_integral = wavelengths.clone().map(|w| cie_xyz(w).y * STEP).sum::<f32>();
+
+        // Column k is the color of the spectrum that is one in band k and zero elsewhere.
+        let mut band_colors = [Color::ZERO; 3];
+        for w in wavelengths {
+            band_colors[band(w)] += xyz_to_rgb(cie_xyz(w)) * STEP / y_integral;
+        }
+        let bands_to_rgb = Mat3::from_cols(band_colors[0], band_colors[1], band_colors[2]);
+        Basis { rgb_to_bands: bands_to_rgb.inverse(), y_integral }
+    })
+}
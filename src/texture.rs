@@ -0,0 +1,148 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use glam::{vec3, Vec3};
+use rand::prelude::*;
+use crate::camera::Color;
+use crate::image::Image;
+use crate::image_io;
+use crate::ray::Point;
+
+// Spatially varying color, looked up by surface coordinates (u, v) and hit point p.
+#[derive(Clone, Debug)]
+pub enum Texture {
+    Solid(Color),
+    // 3D checker pattern with cubes of side scale.
+    Checker {
+        inv_scale: f32,
+        even: Box<Texture>,
+        odd: Box<Texture>,
+    },
+    Image(Arc<Image>),
+    // Smooth gray Perlin noise.
+    Noise {
+        perlin: Arc<Perlin>,
+        scale: f32,
+    },
+    // Perlin turbulence phase-shifting sine stripes.
+    Marble {
+        perlin: Arc<Perlin>,
+        scale: f32,
+    },
+}
+
+impl Texture {
+    pub fn checker(scale: f32, even: Texture, odd: Texture) -> Texture {
+        Texture::Checker { inv_scale: 1.0 / scale, even: Box::new(even), odd: Box::new(odd) }
+    }
+
+    // Image texture loaded from an .hdr, .pfm or .ppm file.
+    pub fn image<P: AsRef<Path>>(path: P) -> io::Result<Texture> {
+        Ok(Texture::Image(Arc::new(image_io::load(path)?)))
+    }
+
+    pub fn noise<R: Rng>(scale: f32, rng: &mut R) -> Texture {
+        Texture::Noise { perlin: Arc::new(Perlin::new(rng)), scale }
+    }
+
+    pub fn marble<R: Rng>(scale: f32, rng: &mut R) -> Texture {
+        Texture::Marble { perlin: Arc::new(Perlin::new(rng)), scale }
+    }
+
+    pub fn value(&self, u: f32, v: f32, p: Point) -> Color {
+        match self {
+            Texture::Solid(color) => *color,
+            Texture::Checker { inv_scale, even, odd } => {
+                let cell = (*inv_scale * p).floor();
+                let is_even = (cell.x as i64 + cell.y as i64 + cell.z as i64) % 2 == 0;
+                if is_even { even.value(u, v, p) } else { odd.value(u, v, p) }
+            }
+            Texture::Image(image) => image.sample(u, v),
+            Texture::Noise { perlin, scale } => Color::splat(0.5 * (1.0 + perlin.noise(*scale * p))),
+            Texture::Marble { perlin, scale } => {
+                Color::splat(0.5 * (1.0 + (*scale * p.z + 10.0 * perlin.turbulence(p, 7)).sin()))
+            }
+        }
+    }
+}
+
+impl From<Color> for Texture {
+    fn from(color: Color) -> Texture {
+        Texture::Solid(color)
+    }
+}
+
+const POINT_COUNT: usize = 256;
+
+// Perlin gradient noise over a lattice of random unit vectors.
+#[derive(Debug)]
+pub struct Perlin {
+    random_vectors: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new<R: Rng>(rng: &mut R) -> Perlin {
+        let random_vectors = (0..POINT_COUNT)
+            .map(|_| loop {
+                let v = vec3(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+                if v.length_squared() > 1e-6 {
+                    break v.normalize();
+                }
+            })
+            .collect();
+        let mut permutation = || {
+            let mut p = (0..POINT_COUNT).collect::<Vec<usize>>();
+            p.shuffle(rng);
+            p
+        };
+        let perm_x = permutation();
+        let perm_y = permutation();
+        let perm_z = permutation();
+        Perlin { random_vectors, perm_x, perm_y, perm_z }
+    }
+
+    // Noise value in roughly [-1, 1].
+    pub fn noise(&self, p: Point) -> f32 {
+        let f = p - p.floor();
+        let i = p.x.floor() as i64;
+        let j = p.y.floor() as i64;
+        let k = p.z.floor() as i64;
+
+        // Hermite smoothing of the interpolation weights
+        let w = f * f * (3.0 - 2.0 * f);
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[((i + di) & 255) as usize]
+                        ^ self.perm_y[((j + dj) & 255) as usize]
+                        ^ self.perm_z[((k + dk) & 255) as usize];
+                    let (di, dj, dk) = (di as f32, dj as f32, dk as f32);
+                    let weight = f - vec3(di, dj, dk);
+                    accum += (di * w.x + (1.0 - di) * (1.0 - w.x))
+                        * (dj * w.y + (1.0 - dj) * (1.0 - w.y))
+                        * (dk * w.z + (1.0 - dk) * (1.0 - w.z))
+                        * self.random_vectors[index].dot(weight);
+                }
+            }
+        }
+        accum
+    }
+
+    // Sum of depth octaves of noise, each at double the frequency and half the weight.
+    pub fn turbulence(&self, p: Point, depth: u32) -> f32 {
+        let mut accum = 0.0;
+        let mut temp_p = p;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accum += weight * self.noise(temp_p);
+            weight *= 0.5;
+            temp_p *= 2.0;
+        }
+        accum.abs()
+    }
+}
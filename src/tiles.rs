@@ -0,0 +1,251 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use crate::camera::{splitmix64, Camera, Color};
+use crate::hittable::Hittable;
+use crate::image::Image;
+use crate::render::{CancelToken, RenderError, RenderOptions, RenderProgress};
+
+pub const TILE_SIZE: u32 = 32;
+
+// Square block of pixels, the unit of work (and of checkpointing) of a render.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Splits the image into tiles, row by row. Tiles at the right and bottom edges may be smaller.
+pub fn tiles(image_width: u32, image_height: u32) -> Vec<Tile> {
+    (0..image_height).step_by(TILE_SIZE as usize)
+        .flat_map(|y| (0..image_width).step_by(TILE_SIZE as usize).map(move |x| Tile {
+            x,
+            y,
+            width: TILE_SIZE.min(image_width - x),
+            height: TILE_SIZE.min(image_height - y),
+        }))
+        .collect()
+}
+
+// Where and how often a render saves its progress.
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    pub path: PathBuf,
+    // Minimum time between two saves. Zero saves after every tile.
+    pub interval: Duration,
+    // Identifies the world being rendered, e.g. a hash of the scene file. The camera settings
+    // are checked by the render itself, but the world is not, so progress saved under
+    // another key is thrown away.
+    pub key: u64,
+}
+
+impl Checkpoint {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Checkpoint {
+        Checkpoint { path: path.into(), interval: Duration::from_secs(60), key: 0 }
+    }
+}
+
+// Sample sums of the finished tiles.
+struct Progress {
+    fingerprint: u64,
+    tile_done: Vec<bool>,
+    sums: Vec<Color>,
+}
+
+const CHECKPOINT_MAGIC: &[u8; 8] = b"RTCKPT1\n";
+
+impl Progress {
+    fn new(camera: &Camera, tile_count: usize, key: u64) -> Progress {
+        Progress {
+            fingerprint: splitmix64(camera.fingerprint() ^ key),
+            tile_done: vec![false; tile_count],
+            sums: vec![Color::ZERO; camera.image_width as usize * camera.image_height as usize],
+        }
+    }
+
+    // Layout: magic, fingerprint, tile count, pixel count, one byte per tile, then the
+    // little-endian RGB sums of every pixel.
+    fn save(&self, checkpoint: &Checkpoint) -> io::Result<()> {
+        // Write next to the target and rename, so a crash while saving keeps the old checkpoint.
+        let mut temp_name = checkpoint.path.clone().into_os_string();
+        temp_name.push(".tmp");
+        let temp_path = PathBuf::from(temp_name);
+
+        let mut out = BufWriter::new(File::create(&temp_path)?);
+        out.write_all(CHECKPOINT_MAGIC)?;
+        out.write_all(&self.fingerprint.to_le_bytes())?;
+        out.write_all(&(self.tile_done.len() as u64).to_le_bytes())?;
+        out.write_all(&(self.sums.len() as u64).to_le_bytes())?;
+        out.write_all(&self.tile_done.iter().map(|&done| done as u8).collect::<Vec<u8>>())?;
+        for sum in &self.sums {
+            for c in [sum.x, sum.y, sum.z] {
+                out.write_all(&c.to_le_bytes())?;
+            }
+        }
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&temp_path, &checkpoint.path)
+    }
+
+    // Reads a checkpoint, or None if it was written for a different render.
+    fn load(checkpoint: &Checkpoint, expected: &Progress) -> io::Result<Option<Progress>> {
+        let mut input = BufReader::new(File::open(&checkpoint.path)?);
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != CHECKPOINT_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a render checkpoint"));
+        }
+        let mut read_u64 = || -> io::Result<u64> {
+            let mut bytes = [0u8; 8];
+            input.read_exact(&mut bytes)?;
+            Ok(u64::from_le_bytes(bytes))
+        };
+        let fingerprint = read_u64()?;
+        let tile_count = read_u64()? as usize;
+        let pixel_count = read_u64()? as usize;
+        if fingerprint != expected.fingerprint
+            || tile_count != expected.tile_done.len()
+            || pixel_count != expected.sums.len() {
+            return Ok(None);
+        }
+
+        let mut tile_bytes = vec![0u8; tile_count];
+        input.read_exact(&mut tile_bytes)?;
+        let mut sum_bytes = vec![0u8; pixel_count * 12];
+        input.read_exact(&mut sum_bytes)?;
+        let sums = sum_bytes.chunks(12)
+            .map(|b| {
+                let c = |o: usize| f32::from_le_bytes([b[o], b[o + 1], b[o + 2], b[o + 3]]);
+                Color::new(c(0), c(4), c(8))
+            })
+            .collect();
+        Ok(Some(Progress { fingerprint, tile_done: tile_bytes.iter().map(|&b| b != 0).collect(), sums }))
+    }
+}
+
+impl Camera {
+    // Like render, but saves the finished tiles to a checkpoint file as it goes. If the
+    // file already holds progress of the same render, only the missing tiles are rendered,
+    // giving the same image as an uninterrupted render. The file is removed once the
+    // render is done.
+    //
+    // Besides the camera settings, the checkpoint only records its key, which has to change
+    // with the world for a changed world to be noticed.
+    pub fn render_resumable<W: Hittable + Sync>(&self, world: &W, checkpoint: &Checkpoint) -> io::Result<Image> {
+        let options = RenderOptions { checkpoint: Some(checkpoint.clone()), ..RenderOptions::default() };
+        match self.render_with(world, &options) {
+            Ok(image) => Ok(image),
+            Err(RenderError::Io(e)) => Err(e),
+            Err(RenderError::Cancelled) => unreachable!("Render without a cancel token was cancelled."),
+        }
+    }
+
+    // Renders tile by tile, reporting progress, stopping when cancelled and checkpointing
+    // as set in the options.
+    pub fn render_with<W: Hittable + Sync>(&self, world: &W, options: &RenderOptions) -> Result<Image, RenderError> {
+        let tiles = tiles(self.image_width, self.image_height);
+        let key = options.checkpoint.as_ref().map_or(0, |checkpoint| checkpoint.key);
+        let mut progress = Progress::new(self, tiles.len(), key);
+        if let Some(checkpoint) = &options.checkpoint {
+            if checkpoint.path.exists() {
+                if let Some(saved) = Progress::load(checkpoint, &progress)? {
+                    progress = saved;
+                }
+            }
+        }
+
+        let pending = tiles.iter()
+            .enumerate()
+            .filter(|(index, _)| !progress.tile_done[*index])
+            .map(|(index, tile)| (index, *tile))
+            .collect::<Vec<(usize, Tile)>>();
+
+        let pixels_total = self.image_width as u64 * self.image_height as u64;
+        let pixels_pending = pending.iter().map(|(_, tile)| tile.width as u64 * tile.height as u64).sum::<u64>();
+        let report = RenderProgress {
+            tiles_done: tiles.len() - pending.len(),
+            tiles_total: tiles.len(),
+            pixels_done: pixels_total - pixels_pending,
+            pixels_total,
+            elapsed: Duration::ZERO,
+            eta: None,
+        };
+        if let Some(on_progress) = &options.on_progress {
+            on_progress(&report);
+        }
+
+        let render_start = Instant::now();
+        let state = Mutex::new((progress, report, render_start));
+        let result = pending.into_par_iter().try_for_each(|(index, tile)| {
+            let sums = self.render_tile(world, tile, options.cancel.as_ref()).ok_or(RenderError::Cancelled)?;
+            let mut guard = state.lock().expect("Another tile panicked.");
+            let (progress, report, last_save) = &mut *guard;
+            for (row, tile_row) in sums.chunks(tile.width as usize).enumerate() {
+                let start = (tile.y as usize + row) * self.image_width as usize + tile.x as usize;
+                progress.sums[start..start + tile_row.len()].copy_from_slice(tile_row);
+            }
+            progress.tile_done[index] = true;
+
+            if let Some(on_progress) = &options.on_progress {
+                report.tiles_done += 1;
+                report.pixels_done += tile.width as u64 * tile.height as u64;
+                report.elapsed = render_start.elapsed();
+                // Pixels rendered in this run, restored ones took no time
+                let rendered = pixels_pending - (report.pixels_total - report.pixels_done);
+                report.eta = Some(report.elapsed.mul_f64((report.pixels_total - report.pixels_done) as f64 / rendered as f64));
+                on_progress(report);
+            }
+
+            match &options.checkpoint {
+                Some(checkpoint) if last_save.elapsed() >= checkpoint.interval => {
+                    *last_save = Instant::now();
+                    progress.save(checkpoint)?;
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        });
+
+        let (progress, _, _) = state.into_inner().expect("Another tile panicked.");
+        if let Some(checkpoint) = &options.checkpoint {
+            match result {
+                // Keep the finished tiles for the next run
+                Err(RenderError::Cancelled) => progress.save(checkpoint)?,
+                Ok(()) => match fs::remove_file(&checkpoint.path) {
+                    // Fast renders may finish before the first save
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                    _ => (),
+                },
+                Err(RenderError::Io(_)) => (),
+            }
+        }
+        result?;
+
+        let pixels = progress.sums.iter().map(|sum| *sum / self.samples_per_pixel as f32).collect();
+        Ok(Image::from_pixels(self.image_width, self.image_height, pixels))
+    }
+
+    // Sample sums of the pixels in the tile, row by row, or None if the render is cancelled
+    // before the tile is done. Checking every row keeps large tiles from delaying it.
+    fn render_tile<W: Hittable>(&self, world: &W, tile: Tile, cancel: Option<&CancelToken>) -> Option<Vec<Color>> {
+        let mut sums = Vec::with_capacity(tile.width as usize * tile.height as usize);
+        for j in tile.y..tile.y + tile.height {
+            if cancel.is_some_and(CancelToken::is_cancelled) {
+                return None;
+            }
+            for i in tile.x..tile.x + tile.width {
+                let mut rng = self.pixel_rng(i, j, 0);
+                let sum = (0..self.samples_per_pixel)
+                    .map(|_| self.sample_pixel(i, j, world, &mut rng))
+                    .sum::<Color>();
+                sums.push(sum);
+            }
+        }
+        Some(sums)
+    }
+}
@@ -0,0 +1,58 @@
+use crate::camera::Color;
+use crate::image::Image;
+use crate::interval::Interval;
+
+// Turns linear radiance into display values: gamma encoding, clamping and quantization.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneMap {
+    pub gamma: f32,
+}
+
+// 8-bit RGB bytes of an image, row by row.
+pub struct Quantized {
+    pub rgb: Vec<u8>,
+    // Pixels with a NaN or infinite component, written as black.
+    pub invalid_pixels: usize,
+}
+
+impl Default for ToneMap {
+    // Gamma 2, i.e. the square root of the linear value.
+    fn default() -> ToneMap {
+        ToneMap { gamma: 2.0 }
+    }
+}
+
+impl ToneMap {
+    pub fn new(gamma: f32) -> ToneMap {
+        assert!(gamma > 0.0, "Gamma must be positive.");
+        ToneMap { gamma }
+    }
+
+    // Gamma encoded color in [0, 1], or None for pixels containing NaN or infinity.
+    pub fn map(&self, color: &Color) -> Option<Color> {
+        if !color.is_finite() {
+            return None;
+        }
+        let unit = Interval::new(0.0, 1.0);
+        let encode = |c: f32| unit.clamp(c).powf(1.0 / self.gamma);
+        Some(Color::new(encode(color.x), encode(color.y), encode(color.z)))
+    }
+
+    // Inverse of the gamma encoding, turning display values back into linear ones.
+    pub fn unmap(&self, color: &Color) -> Color {
+        Color::new(color.x.powf(self.gamma), color.y.powf(self.gamma), color.z.powf(self.gamma))
+    }
+
+    pub fn quantize(&self, image: &Image) -> Quantized {
+        let mut invalid_pixels = 0;
+        let mut rgb = Vec::with_capacity(image.pixels().len() * 3);
+        for pixel in image.pixels() {
+            let mapped = self.map(pixel).unwrap_or_else(|| {
+                invalid_pixels += 1;
+                Color::ZERO
+            });
+            rgb.extend([mapped.x, mapped.y, mapped.z].map(|c| (c * 255.0).round() as u8));
+        }
+        Quantized { rgb, invalid_pixels }
+    }
+}
@@ -0,0 +1,46 @@
+// The BVH only skips shapes a ray cannot hit, so it must render exactly what the flat list
+// of shapes renders.
+
+use glam::vec3;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::CameraBuilder;
+use ray_tracing::hittable::Shape;
+use ray_tracing::material::Material::*;
+
+// Small spheres of every material scattered over a ground plane, from a fixed seed
+fn scattered_spheres() -> Vec<Shape> {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut world = (0..60)
+        .map(|_| {
+            let center = vec3(rng.gen_range(-6.0..6.0), 0.25, rng.gen_range(-6.0..2.0));
+            let color = vec3(rng.gen(), rng.gen(), rng.gen());
+            let material = match rng.gen_range(0..3) {
+                0 => Lambertian(color.into()),
+                1 => Metal(color.into(), rng.gen_range(0.0..0.5)),
+                _ => Dielectric(1.5.into()),
+            };
+            Shape::new_sphere(center, 0.25, &material)
+        })
+        .collect::<Vec<Shape>>();
+    world.push(Shape::new_plane(vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), &Lambertian(vec3(0.5, 0.5, 0.5).into())));
+    world.push(Shape::new_quad(vec3(-2.0, 0.0, -7.0), vec3(4.0, 0.0, 0.0), vec3(0.0, 3.0, 0.0), &Metal(vec3(0.8, 0.8, 0.8).into(), 0.0)));
+    world
+}
+
+#[test]
+fn bvh_renders_the_same_image_as_the_flat_list() {
+    let camera = CameraBuilder::default()
+        .set_image_width(64)
+        .set_samples_per_pixel(4)
+        .set_max_depth(16)
+        .set_vfov(40.0)
+        .set_view_direction(vec3(0.0, 2.0, 6.0), vec3(0.0, 0.0, -2.0))
+        .set_seed(3)
+        .build();
+
+    let flat = scattered_spheres();
+    let bvh = Bvh::new(scattered_spheres());
+    assert_eq!(camera.render(&flat).pixels(), camera.render(&bvh).pixels());
+}
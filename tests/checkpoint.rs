@@ -0,0 +1,133 @@
+// Interrupted renders resumed from a checkpoint give the same image as uninterrupted ones.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use glam::Vec3;
+use rand::Rng;
+use ray_tracing::aabb::Aabb;
+use ray_tracing::background::Background;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Camera, Color};
+use ray_tracing::hittable::{HitRecord, Hittable, Shape};
+use ray_tracing::interval::Interval;
+use ray_tracing::material::Material;
+use ray_tracing::ray::Ray;
+use ray_tracing::render::{RenderOptions, RenderProgress};
+use ray_tracing::tiles::Checkpoint;
+
+mod common;
+
+// Counts hit tests, and panics (like a crashing render) once told to.
+struct Counting<'a> {
+    world: &'a Bvh<Shape>,
+    hits: AtomicUsize,
+    crash: AtomicBool,
+}
+
+impl<'a> Counting<'a> {
+    fn new(world: &'a Bvh<Shape>) -> Counting<'a> {
+        Counting { world, hits: AtomicUsize::new(0), crash: AtomicBool::new(false) }
+    }
+}
+
+impl Hittable for Counting<'_> {
+    fn hit<R: Rng>(&self, ray: &Ray, interval: Interval, rng: &mut R) -> Option<(HitRecord, &Material)> {
+        if self.crash.load(Ordering::Relaxed) {
+            panic!("simulated crash");
+        }
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        self.world.hit(ray, interval, rng)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.world.bounding_box()
+    }
+}
+
+fn scene(seed: u64) -> (Camera, Bvh<Shape>) {
+    let (mut camera, world) = common::tiled_scene();
+    (camera.set_seed(seed).build(), world)
+}
+
+// Renders until the first tile is done and saved, then crashes.
+fn crash_after_first_tile(camera: &Camera, world: &Bvh<Shape>, checkpoint: &Checkpoint) {
+    let crashing_world = Counting::new(world);
+    let options = RenderOptions {
+        // Called before the finished tile is saved, which happens on the same thread
+        on_progress: Some(Box::new(|progress: &RenderProgress| {
+            if progress.tiles_done > 0 {
+                crashing_world.crash.store(true, Ordering::Relaxed);
+            }
+        })),
+        checkpoint: Some(checkpoint.clone()),
+        ..RenderOptions::default()
+    };
+    let crashed = panic::catch_unwind(AssertUnwindSafe(|| camera.render_with(&crashing_world, &options)));
+    assert!(crashed.is_err(), "The render should have crashed.");
+    assert!(checkpoint.path.exists(), "The finished tiles should have been saved.");
+}
+
+fn checkpoint(name: &str) -> Checkpoint {
+    let path = std::env::temp_dir().join(format!("ray_tracing_{}_{}.ckpt", name, std::process::id()));
+    Checkpoint { path, interval: Duration::ZERO, key: 0 }
+}
+
+#[test]
+fn resumed_render_matches_uninterrupted_render() {
+    let (camera, world) = scene(1);
+    let full_world = Counting::new(&world);
+    camera.render(&full_world);
+    let full_hits = full_world.hits.into_inner();
+
+    let checkpoint = checkpoint("resume");
+    crash_after_first_tile(&camera, &world, &checkpoint);
+    let resumed_world = Counting::new(&world);
+    let resumed = camera.render_resumable(&resumed_world, &checkpoint).expect("Resuming should work.");
+    assert!(resumed_world.hits.into_inner() < full_hits, "Saved tiles should not be rendered again.");
+    assert_eq!(resumed, camera.render(&world));
+    assert!(!checkpoint.path.exists(), "A finished render removes its checkpoint.");
+}
+
+#[test]
+fn checkpoint_of_other_render_is_ignored() {
+    let (camera, world) = scene(1);
+    let (other, _) = scene(99);
+    let checkpoint = checkpoint("other");
+    crash_after_first_tile(&other, &world, &checkpoint);
+
+    let image = camera.render_resumable(&world, &checkpoint).expect("Rendering should work.");
+    assert_eq!(image, camera.render(&world));
+}
+
+#[test]
+fn checkpoint_with_other_background_or_lights_is_ignored() {
+    let (camera, world) = scene(1);
+    let light = Shape::new_sphere(Vec3::Y, 0.5, &Material::DiffuseLight(Color::splat(4.0)));
+    let (mut other_background, _) = common::tiled_scene();
+    let other_background = other_background.set_seed(1).set_background(Background::Solid(Color::new(1.0, 0.0, 0.0)));
+    let (mut other_lights, _) = common::tiled_scene();
+    let other_lights = other_lights.set_seed(1).set_lights(vec![light]);
+
+    let expected = camera.render(&world);
+    for (name, other) in [("background", other_background), ("lights", other_lights)] {
+        let checkpoint = checkpoint(name);
+        crash_after_first_tile(&other.build(), &world, &checkpoint);
+        let image = camera.render_resumable(&world, &checkpoint).expect("Rendering should work.");
+        assert_eq!(image, expected, "{}", name);
+    }
+}
+
+#[test]
+fn checkpoint_of_changed_world_is_ignored() {
+    let (camera, world) = scene(1);
+    // A red room around the camera changes every pixel
+    let changed = Bvh::new(vec![Shape::new_sphere(Vec3::ZERO, 50.0, &Material::Lambertian(Vec3::X.into()))]);
+    let saved = Checkpoint { key: 1, ..checkpoint("changed") };
+    crash_after_first_tile(&camera, &world, &saved);
+
+    let resumed = Checkpoint { key: 2, ..saved };
+    let image = camera.render_resumable(&changed, &resumed).expect("Rendering should work.");
+    assert_eq!(image, camera.render(&changed));
+}
@@ -0,0 +1,47 @@
+// Helpers shared by the integration tests. Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use glam::Vec3;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::CameraBuilder;
+use ray_tracing::hittable::{HitRecord, Hittable, Shape};
+use ray_tracing::interval::Interval;
+use ray_tracing::material::Material;
+use ray_tracing::ray::Ray;
+use ray_tracing::scene::read_scene;
+
+// The custom scene, small enough to render quickly as 3 x 2 tiles.
+pub fn tiled_scene() -> (CameraBuilder, Bvh<Shape>) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes").join("custom.scene");
+    let (mut camera, world) = read_scene(path).expect("Scene should load.");
+    let camera = camera.set_image_width(96).set_samples_per_pixel(4).set_max_depth(8);
+    (camera, Bvh::new(world))
+}
+
+// For shapes whose material does not matter
+pub fn material() -> Material {
+    Material::Lambertian(Vec3::ONE.into())
+}
+
+pub fn assert_close(a: Vec3, b: Vec3) {
+    assert!((a - b).length() < 1e-4, "{} != {}", a, b);
+}
+
+// Everything in front of the ray origin, like camera rays
+pub const ALL: Interval = Interval { min: 0.001, max: f32::INFINITY };
+
+pub fn hit<H: Hittable>(shape: &H, orig: Vec3, dir: Vec3) -> (HitRecord, &Material) {
+    try_hit(shape, orig, dir).expect("Ray should hit the shape.")
+}
+
+pub fn misses<H: Hittable>(shape: &H, orig: Vec3, dir: Vec3) -> bool {
+    try_hit(shape, orig, dir).is_none()
+}
+
+fn try_hit<H: Hittable>(shape: &H, orig: Vec3, dir: Vec3) -> Option<(HitRecord, &Material)> {
+    shape.hit(&Ray::new(orig, dir), ALL, &mut SmallRng::seed_from_u64(0))
+}
@@ -0,0 +1,166 @@
+// Combined solids must report the surface where a ray really enters or leaves them, with
+// the material of the operand that surface belongs to.
+
+use std::path::Path;
+
+use glam::{vec3, Vec3};
+use ray_tracing::csg::CsgOperation::*;
+use ray_tracing::hittable::{Hittable, Shape};
+use ray_tracing::material::Material;
+use ray_tracing::scene::parse_scene;
+
+mod common;
+use common::{assert_close, hit, misses};
+
+// Materials tell the operands apart
+fn red() -> Material {
+    Material::Lambertian(Vec3::X.into())
+}
+
+fn green() -> Material {
+    Material::Lambertian(Vec3::Y.into())
+}
+
+fn is_red(material: &Material) -> bool {
+    matches!(material, Material::Lambertian(texture) if texture.value(0.0, 0.0, Vec3::ZERO) == Vec3::X)
+}
+
+// Two unit spheres overlapping between z = -0.5 and z = 0.5
+fn overlapping(operation: ray_tracing::csg::CsgOperation) -> Shape {
+    Shape::new_csg(
+        operation,
+        vec![Shape::new_sphere(vec3(0.0, 0.0, -0.5), 1.0, &red())],
+        vec![Shape::new_sphere(vec3(0.0, 0.0, 0.5), 1.0, &green())])
+}
+
+#[test]
+fn union_skips_inner_surfaces() {
+    let union = overlapping(Union);
+
+    let (entry, material) = hit(&union, vec3(0.0, 0.0, -5.0), Vec3::Z);
+    assert_close(entry.point, vec3(0.0, 0.0, -1.5));
+    assert!(entry.front_face && is_red(material));
+
+    // From inside the overlap, the next surface is the far side of the other sphere
+    let (exit, material) = hit(&union, vec3(0.0, 0.0, 0.0), Vec3::Z);
+    assert_close(exit.point, vec3(0.0, 0.0, 1.5));
+    assert_close(exit.normal, -Vec3::Z);
+    assert!(!exit.front_face && !is_red(material));
+}
+
+#[test]
+fn intersection_is_a_lens() {
+    let lens = overlapping(Intersection);
+
+    // The near face of the lens is the surface of the far sphere
+    let (entry, material) = hit(&lens, vec3(0.0, 0.0, -5.0), Vec3::Z);
+    assert_close(entry.point, vec3(0.0, 0.0, -0.5));
+    assert_close(entry.normal, -Vec3::Z);
+    assert!(entry.front_face && !is_red(material));
+
+    let (exit, material) = hit(&lens, vec3(0.0, 0.0, 0.0), Vec3::Z);
+    assert_close(exit.point, vec3(0.0, 0.0, 0.5));
+    assert!(!exit.front_face && is_red(material));
+
+    // Through a sphere but beside the lens
+    assert!(misses(&lens, vec3(0.0, 0.95, -5.0), Vec3::Z));
+
+    let bbox = lens.bounding_box();
+    assert_close(bbox.min(), vec3(-1.0, -1.0, -0.5));
+    assert_close(bbox.max(), vec3(1.0, 1.0, 0.5));
+}
+
+#[test]
+fn difference_turns_the_cutting_surface_inside_out() {
+    let bitten = overlapping(Difference);
+
+    let (entry, material) = hit(&bitten, vec3(0.0, 0.0, -5.0), Vec3::Z);
+    assert_close(entry.point, vec3(0.0, 0.0, -1.5));
+    assert!(entry.front_face && is_red(material));
+
+    // The bite is the surface of the second sphere, and the solid lies outside of it
+    let (bite, material) = hit(&bitten, vec3(0.0, 0.0, 5.0), -Vec3::Z);
+    assert_close(bite.point, vec3(0.0, 0.0, -0.5));
+    assert_close(bite.normal, Vec3::Z);
+    assert!(bite.front_face && !is_red(material));
+
+    let (exit, _) = hit(&bitten, vec3(0.0, 0.0, -1.0), Vec3::Z);
+    assert_close(exit.point, vec3(0.0, 0.0, -0.5));
+    assert!(!exit.front_face);
+
+    assert_eq!(bitten.bounding_box(), Shape::new_sphere(vec3(0.0, 0.0, -0.5), 1.0, &red()).bounding_box());
+}
+
+#[test]
+fn hollow_and_drilled_spheres() {
+    let hollow = Shape::new_csg(
+        Difference,
+        vec![Shape::new_sphere(Vec3::ZERO, 1.0, &red())],
+        vec![Shape::new_sphere(Vec3::ZERO, 0.5, &green())]);
+    // From the cavity, the ray enters the shell through its inner surface
+    let (inner, material) = hit(&hollow, Vec3::ZERO, Vec3::X);
+    assert_close(inner.point, vec3(0.5, 0.0, 0.0));
+    assert_close(inner.normal, -Vec3::X);
+    assert!(inner.front_face && !is_red(material));
+
+    let drilled = Shape::new_csg(
+        Difference,
+        vec![Shape::new_sphere(Vec3::ZERO, 1.0, &red())],
+        vec![Shape::new_cylinder(vec3(0.0, -2.0, 0.0), vec3(0.0, 2.0, 0.0), 0.25, &green())]);
+    assert!(misses(&drilled, vec3(0.0, 5.0, 0.0), -Vec3::Y));
+    // From inside the hole, sideways into the wall
+    let (wall, material) = hit(&drilled, vec3(0.0, 0.5, 0.0), vec3(1.0, -0.2, 0.0));
+    assert_close(wall.point, vec3(0.25, 0.45, 0.0));
+    assert_close(wall.normal, -Vec3::X);
+    assert!(wall.front_face && !is_red(material));
+}
+
+#[test]
+fn box_cut_by_half_space() {
+    // A plane counts as the half space behind it, below y = 0.5 here
+    let cut = Shape::new_csg(
+        Intersection,
+        Shape::new_box(Vec3::ZERO, Vec3::ONE, &red()),
+        vec![Shape::new_plane(vec3(0.0, 0.5, 0.0), Vec3::Y, &green())]);
+
+    let (top, material) = hit(&cut, vec3(0.5, 5.0, 0.5), -Vec3::Y);
+    assert_close(top.point, vec3(0.5, 0.5, 0.5));
+    assert!(top.front_face && !is_red(material));
+    assert!(misses(&cut, vec3(-1.0, 0.75, 0.5), Vec3::X));
+    assert!(cut.bounding_box().is_bounded());
+
+    // Upwards, the ray starts out behind the plane and leaves the solid through it
+    let (bottom, material) = hit(&cut, vec3(0.5, -5.0, 0.5), Vec3::Y);
+    assert_close(bottom.point, vec3(0.5, 0.0, 0.5));
+    assert!(bottom.front_face && is_red(material));
+    let (top, material) = hit(&cut, vec3(0.5, 0.25, 0.5), Vec3::Y);
+    assert_close(top.point, vec3(0.5, 0.5, 0.5));
+    assert_close(top.normal, -Vec3::Y);
+    assert!(!top.front_face && !is_red(material));
+
+    // Parallel to the plane and behind it, the ray never crosses it but is inside all along
+    let (side, material) = hit(&cut, vec3(-1.0, 0.25, 0.5), Vec3::X);
+    assert_close(side.point, vec3(0.0, 0.25, 0.5));
+    assert!(side.front_face && is_red(material));
+    let (far_side, _) = hit(&cut, vec3(0.5, 0.25, 0.5), Vec3::X);
+    assert_close(far_side.point, vec3(1.0, 0.25, 0.5));
+    assert!(!far_side.front_face);
+}
+
+#[test]
+fn named_scene_shapes_combine() {
+    let source = "material white lambertian albedo=1,1,1\n\
+                  sphere center=0,0,-0.5 radius=1 material=white name=front\n\
+                  sphere center=0,0,0.5 radius=1 material=white name=back\n\
+                  intersection a=front b=back name=lens\n\
+                  union a=lens b=lens translate=0,0,10";
+    let (_, world) = parse_scene(source, Path::new(".")).expect("Scene should parse.");
+    assert_eq!(world.len(), 1);
+    let (entry, _) = hit(&world[0], vec3(0.0, 0.0, 5.0), Vec3::Z);
+    assert_close(entry.point, vec3(0.0, 0.0, 9.5));
+
+    let error = parse_scene("difference a=missing b=missing", Path::new("."))
+        .err()
+        .expect("Unknown shapes should fail.");
+    assert!(error.to_string().contains("unknown shape 'missing'"), "{}", error);
+}
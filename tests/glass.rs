@@ -0,0 +1,49 @@
+// Tinted glass absorbs light along the distance travelled inside it.
+
+use glam::{vec3, Vec3};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+use ray_tracing::camera::Color;
+use ray_tracing::hittable::HitRecord;
+use ray_tracing::material::Material;
+use ray_tracing::ray::Ray;
+
+// Attenuation of a ray that travelled distance, hitting the surface from the inside
+// (front_face false) or the outside.
+fn attenuation(material: &Material, distance: f32, front_face: bool) -> Color {
+    // Direction not normalized, to check that t is scaled to a distance
+    let ray = Ray::new(Vec3::ZERO, vec3(0.0, 0.0, 2.0));
+    let t = distance / 2.0;
+    // The normal always faces the ray
+    let hit_record = HitRecord { point: ray.at(t), normal: vec3(0.0, 0.0, -1.0), t, front_face, u: 0.0, v: 0.0 };
+    let (_, attenuation) = material.scatter(&ray, &hit_record, &mut SmallRng::seed_from_u64(0)).expect("Glass always scatters.");
+    attenuation
+}
+
+fn assert_close(a: Color, b: Color) {
+    assert!((a - b).abs().max_element() < 1e-5, "{} != {}", a, b);
+}
+
+#[test]
+fn absorption_follows_beer_lambert() {
+    let tint = Color::new(0.5, 0.8, 1.0);
+    let glass = Material::tinted_glass(1.5, tint, 1.0);
+
+    assert_close(attenuation(&glass, 1.0, false), tint);
+    assert_close(attenuation(&glass, 2.0, false), tint * tint);
+    assert_close(attenuation(&glass, 0.0, false), Color::ONE);
+    // Light arriving from outside travelled through air
+    assert_close(attenuation(&glass, 3.0, true), Color::ONE);
+}
+
+#[test]
+fn tint_is_given_for_a_distance() {
+    let glass = Material::tinted_glass(1.5, Color::splat(0.25), 2.0);
+    assert_close(attenuation(&glass, 2.0, false), Color::splat(0.25));
+    assert_close(attenuation(&glass, 1.0, false), Color::splat(0.5));
+}
+
+#[test]
+fn clear_dielectric_does_not_absorb() {
+    assert_close(attenuation(&Material::Dielectric(1.5.into()), 10.0, false), Color::ONE);
+}
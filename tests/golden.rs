@@ -0,0 +1,151 @@
+// Golden-image regression tests.
+//
+// Every scene in scenes/ is rendered small, with a fixed seed, and compared against the
+// reference image of the same name in tests/golden/; a scene without one fails. The
+// progressive renderer gets its own reference, of the Cornell box like examples/progressive.rs.
+// A failing comparison writes the render and a diff image to target/golden/. After an intended change in output, regenerate the references with
+//
+//     UPDATE_GOLDEN=1 cargo test --test golden
+
+use std::path::{Path, PathBuf};
+
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Camera, Color};
+use ray_tracing::hittable::Shape;
+use ray_tracing::image::Image;
+use ray_tracing::image_io;
+use ray_tracing::progressive::Convergence;
+use ray_tracing::scene::read_scene;
+use ray_tracing::tonemap::ToneMap;
+
+const WIDTH: u32 = 64;
+const SAMPLES: u32 = 8;
+const DEPTH: u32 = 8;
+const SEED: u64 = 1;
+
+// Renders are bit-identical on one platform, but floating point math libraries differ
+// slightly between platforms, which can send the odd path in another direction.
+const MIN_PSNR: f64 = 35.0;
+
+fn load_scene(name: &str) -> (Camera, Bvh<Shape>) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes").join(format!("{}.scene", name));
+    let (mut camera, world) = read_scene(&path).expect("Scene should load.");
+    let camera = camera
+        .set_image_width(WIDTH)
+        .set_samples_per_pixel(SAMPLES)
+        .set_max_depth(DEPTH)
+        .set_seed(SEED)
+        .build();
+    (camera, Bvh::new(world))
+}
+
+fn render_scene(name: &str) -> Image {
+    let (camera, world) = load_scene(name);
+    camera.render(&world)
+}
+
+// Root mean square error between the display (tone mapped) values of two images.
+fn rmse(a: &Image, b: &Image) -> f64 {
+    let tone_map = ToneMap::default();
+    let display = |c: &Color| tone_map.map(c).unwrap_or(Color::ZERO);
+    let sum = a.pixels().iter()
+        .zip(b.pixels())
+        .map(|(pa, pb)| (display(pa) - display(pb)).length_squared() as f64)
+        .sum::<f64>();
+    (sum / (3 * a.pixels().len()) as f64).sqrt()
+}
+
+fn psnr(rmse: f64) -> f64 {
+    if rmse == 0.0 { f64::INFINITY } else { 20.0 * (1.0 / rmse).log10() }
+}
+
+// Absolute difference, scaled up so that small errors are visible.
+fn diff_image(a: &Image, b: &Image) -> Image {
+    let pixels = a.pixels().iter()
+        .zip(b.pixels())
+        .map(|(pa, pb)| ((*pa - *pb).abs() * 4.0).min(Color::ONE))
+        .collect();
+    Image::from_pixels(a.width(), a.height(), pixels)
+}
+
+fn output_dir() -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("golden");
+    std::fs::create_dir_all(&dir).expect("Should be able to create the output directory.");
+    dir
+}
+
+// Names of the scene files in scenes/, in order.
+fn scene_names() -> Vec<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+    let mut names = std::fs::read_dir(dir)
+        .expect("Scene directory should be readable.")
+        .map(|entry| entry.expect("Scene directory should be readable.").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "scene"))
+        .map(|path| path.file_stem().expect("Scene files have names.").to_string_lossy().into_owned())
+        .collect::<Vec<String>>();
+    names.sort();
+    names
+}
+
+// Compares the render against the reference of that name, or saves it as the reference when
+// UPDATE_GOLDEN is set.
+fn check_against_reference(name: &str, actual: Image) -> Result<(), String> {
+    let reference_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.pfm", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        image_io::save(&actual, &reference_path).expect("Should be able to write the reference.");
+        return Ok(());
+    }
+
+    let reference = image_io::load(&reference_path)
+        .map_err(|e| format!("{}: missing reference image ({}), run with UPDATE_GOLDEN=1 to create it.", name, e))?;
+    if (actual.width(), actual.height()) != (reference.width(), reference.height()) {
+        return Err(format!("{}: size changed", name));
+    }
+
+    let error = rmse(&actual, &reference);
+    let quality = psnr(error);
+    if quality < MIN_PSNR {
+        let dir = output_dir();
+        let actual_path = dir.join(format!("{}.png", name));
+        let diff_path = dir.join(format!("{}-diff.png", name));
+        image_io::save(&actual, &actual_path).expect("Should be able to write the render.");
+        image_io::save(&diff_image(&actual, &reference), &diff_path).expect("Should be able to write the diff.");
+        return Err(format!(
+            "{}: render differs from the reference (RMSE {:.4}, PSNR {:.1} dB < {} dB). See {} and {}.",
+            name, error, quality, MIN_PSNR, actual_path.display(), diff_path.display()));
+    }
+    Ok(())
+}
+
+#[test]
+fn golden_scenes() {
+    let names = scene_names();
+    assert!(!names.is_empty(), "No scenes found.");
+    // Check them all before failing, to see every scene that changed at once
+    let failures = names.iter()
+        .filter_map(|name| check_against_reference(name, render_scene(name)).err())
+        .collect::<Vec<String>>();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn golden_progressive() {
+    // Without a time budget, the passes and the pixels they sample do not depend on speed.
+    let (camera, world) = load_scene("cornell_box");
+    let convergence = Convergence { samples_per_pass: 2, min_samples: 4, target_noise: Some(0.2), time_budget: None };
+    let image = camera.render_progressive(&world, &convergence, |_| {});
+    check_against_reference("progressive", image).unwrap_or_else(|e| panic!("{}", e));
+}
+
+#[test]
+fn render_does_not_depend_on_thread_count() {
+    let render_with_threads = |threads| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Should be able to create a thread pool.")
+            .install(|| render_scene("custom"))
+    };
+    assert_eq!(render_with_threads(1), render_with_threads(4));
+}
@@ -0,0 +1,135 @@
+// Image files: PNGs must decode with reference decoders, and malformed headers must give
+// errors rather than panics or huge allocations.
+
+use std::io;
+
+use ray_tracing::camera::Color;
+use ray_tracing::image::Image;
+use ray_tracing::image_io::{read_hdr, read_pfm, read_ppm, write_png};
+use ray_tracing::tonemap::ToneMap;
+
+fn assert_invalid(result: io::Result<Image>, message: &str) {
+    match result {
+        Ok(_) => panic!("Expected an error containing '{}'.", message),
+        Err(error) => {
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            assert!(error.to_string().contains(message), "{}", error);
+        }
+    }
+}
+
+#[test]
+fn hdr_without_pixels_is_rejected() {
+    let header = |resolution: &str| format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n{}\n", resolution).into_bytes();
+    assert_invalid(read_hdr(&header("-Y 1 +X 0")), "HDR image is empty");
+    assert_invalid(read_hdr(&header("-Y 0 +X 1")), "HDR image is empty");
+
+    let mut flat = header("-Y 1 +X 2");
+    flat.extend([128, 64, 32, 129, 0, 0, 0, 0]);
+    let image = read_hdr(&flat).expect("Flat scanlines should load.");
+    assert_eq!((image.width(), image.height()), (2, 1));
+}
+
+#[test]
+fn pfm_size_overflow_is_rejected() {
+    assert_invalid(read_pfm(b"PF\n4294967295 4294967295\n-1.0\n\0\0\0\0"), "PFM image is too large");
+    assert_invalid(read_pfm(b"PF\n2 2\n-1.0\n\0\0\0\0"), "PFM data is truncated");
+}
+
+#[test]
+fn ppm_size_overflow_is_rejected() {
+    assert_invalid(read_ppm(b"P6 4294967295 4294967295 255\n\0\0\0"), "PPM image is too large");
+    assert_invalid(read_ppm(b"P6 2 2 255\n\0\0\0"), "PPM data is truncated");
+}
+
+// Splits a PNG file into its chunks, checking each chunk's CRC.
+fn png_chunks(bytes: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+    let mut rest = &bytes[8..];
+    let mut chunks = Vec::new();
+    while !rest.is_empty() {
+        let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let kind: [u8; 4] = rest[4..8].try_into().unwrap();
+        let data = &rest[8..8 + length];
+        let crc = u32::from_be_bytes(rest[8 + length..12 + length].try_into().unwrap());
+        assert_eq!(crc, crc32fast::hash(&rest[4..8 + length]), "CRC of {}", String::from_utf8_lossy(&kind));
+        chunks.push((kind, data.to_vec()));
+        rest = &rest[12 + length..];
+    }
+    chunks
+}
+
+// Undoes the PNG scanline filters of 8 bit RGB data.
+fn unfilter(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let stride = width * 3;
+    let mut rgb = vec![0u8; stride * height];
+    for y in 0..height {
+        let line = &data[y * (stride + 1)..(y + 1) * (stride + 1)];
+        for i in 0..stride {
+            let a = if i >= 3 { rgb[y * stride + i - 3] } else { 0 } as i16;
+            let b = if y > 0 { rgb[(y - 1) * stride + i] } else { 0 } as i16;
+            let c = if i >= 3 && y > 0 { rgb[(y - 1) * stride + i - 3] } else { 0 } as i16;
+            let predictor = match line[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => (a + b) / 2,
+                4 => {
+                    let p = a + b - c;
+                    let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+                    if pa <= pb && pa <= pc { a } else if pb <= pc { b } else { c }
+                }
+                filter => panic!("Unknown filter {}.", filter),
+            };
+            rgb[y * stride + i] = line[1 + i].wrapping_add(predictor as u8);
+        }
+    }
+    rgb
+}
+
+#[test]
+fn png_decodes_with_reference_inflate() {
+    // Smooth gradients, flat areas and repeats, so that every kind of match is used, and
+    // more data than the 32 KiB window
+    let (width, height) = (200, 100);
+    let pixels = (0..width * height)
+        .map(|i| {
+            let (x, y) = ((i % width) as f32, (i / width) as f32);
+            if y < 30.0 {
+                Color::new(x / width as f32, y / height as f32, 0.5)
+            } else if y < 60.0 {
+                Color::splat(0.25)
+            } else {
+                Color::new(((x as u32 / 7) % 3) as f32 / 2.0, ((y as u32 / 5) % 2) as f32, 0.8)
+            }
+        })
+        .collect();
+    let image = Image::from_pixels(width, height, pixels);
+    let tone_map = ToneMap::default();
+
+    let mut png = Vec::new();
+    write_png(&image, &mut png, &tone_map).expect("Writing to memory should work.");
+    let chunks = png_chunks(&png);
+    let kinds = chunks.iter().map(|(kind, _)| kind).collect::<Vec<_>>();
+    assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+    assert_eq!(&chunks[0].1[..8], [0, 0, 0, 200, 0, 0, 0, 100]);
+
+    // The zlib decoder also checks the Adler-32 checksum.
+    let filtered = miniz_oxide::inflate::decompress_to_vec_zlib(&chunks[1].1).expect("IDAT should inflate.");
+    assert_eq!(filtered.len(), (width as usize * 3 + 1) * height as usize);
+    // Compression should beat storing the data raw by far
+    assert!(chunks[1].1.len() < filtered.len() / 4, "{} bytes", chunks[1].1.len());
+    assert_eq!(unfilter(&filtered, width as usize, height as usize), tone_map.quantize(&image).rgb);
+}
+
+#[test]
+fn png_of_empty_and_tiny_images() {
+    for (width, height) in [(0, 0), (1, 1), (3, 0), (2, 3)] {
+        let image = Image::from_pixels(width, height, vec![Color::new(1.0, 0.5, 0.0); (width * height) as usize]);
+        let mut png = Vec::new();
+        write_png(&image, &mut png, &ToneMap::default()).expect("Writing to memory should work.");
+        let chunks = png_chunks(&png);
+        let filtered = miniz_oxide::inflate::decompress_to_vec_zlib(&chunks[1].1).expect("IDAT should inflate.");
+        assert_eq!(filtered.len(), (width as usize * 3 + 1) * height as usize);
+    }
+}
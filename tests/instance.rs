@@ -0,0 +1,82 @@
+// Instances must behave like the same shapes built directly in world space.
+
+use std::sync::Arc;
+
+use glam::{vec3, Affine3A, Vec3};
+use ray_tracing::hittable::{Hittable, Shape};
+
+mod common;
+use common::{assert_close, hit, material};
+
+#[test]
+fn translated_sphere_matches_moved_sphere() {
+    let offset = vec3(1.0, -2.0, 3.0);
+    let instance = Shape::new_sphere(Vec3::ZERO, 0.5, &material()).transformed(Affine3A::from_translation(offset));
+    let moved = Shape::new_sphere(offset, 0.5, &material());
+
+    for dir in [vec3(0.0, 0.0, 1.0), vec3(0.05, -0.03, 1.0), vec3(-0.1, 0.08, 2.0)] {
+        let orig = offset - vec3(0.0, 0.0, 5.0);
+        let (expected, _) = hit(&moved, orig, dir);
+        let (actual, _) = hit(&instance, orig, dir);
+        assert!((actual.t - expected.t).abs() < 1e-4);
+        assert_close(actual.point, expected.point);
+        assert_close(actual.normal, expected.normal);
+        assert_eq!(actual.front_face, expected.front_face);
+    }
+    assert_close(instance.bounding_box().min(), moved.bounding_box().min());
+    assert_close(instance.bounding_box().max(), moved.bounding_box().max());
+}
+
+#[test]
+fn rotated_box_has_rotated_normals() {
+    let unit_box = Shape::new_box(vec3(-1.0, -1.0, -1.0), vec3(1.0, 1.0, 1.0), &material());
+    let rotated = Shape::new_instance(unit_box, Affine3A::from_rotation_y(45f32.to_radians()));
+
+    // Looking down -x at the edge of the rotated cube, at distance sqrt(2) from its center
+    let (edge, _) = hit(&rotated, vec3(5.0, 0.0, 0.1), vec3(-1.0, 0.0, 0.0));
+    assert!((edge.point.x - (2f32.sqrt() - 0.1)).abs() < 1e-4);
+    assert_close(edge.normal, vec3(1.0, 0.0, 1.0).normalize());
+
+    let bbox = rotated.bounding_box();
+    assert!((bbox.max().x - 2f32.sqrt()).abs() < 1e-3);
+    assert!((bbox.max().y - 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn scaled_sphere_normals_stay_perpendicular() {
+    // Ellipsoid x^2/4 + y^2 + z^2 = 1
+    let ellipsoid = Shape::new_sphere(Vec3::ZERO, 1.0, &material()).transformed(Affine3A::from_scale(vec3(2.0, 1.0, 1.0)));
+
+    let (side, _) = hit(&ellipsoid, vec3(10.0, 0.0, 0.0), vec3(-1.0, 0.0, 0.0));
+    assert_close(side.point, vec3(2.0, 0.0, 0.0));
+    assert_close(side.normal, Vec3::X);
+
+    let (top, _) = hit(&ellipsoid, vec3(1.0, 10.0, 0.0), vec3(0.0, -1.0, 0.0));
+    let p = top.point;
+    assert!((p.x * p.x / 4.0 + p.y * p.y + p.z * p.z - 1.0).abs() < 1e-4);
+    // Gradient of the implicit surface
+    assert_close(top.normal, vec3(p.x / 4.0, p.y, p.z).normalize());
+}
+
+#[test]
+fn transformed_instances_share_their_object() {
+    let ball = Shape::new_instance(vec![Shape::new_sphere(Vec3::ZERO, 1.0, &material())], Affine3A::from_scale(Vec3::splat(2.0)));
+    let moved = ball.transformed(Affine3A::from_translation(vec3(10.0, 0.0, 0.0)));
+    match (&ball, &moved) {
+        (Shape::Instance { object: a, .. }, Shape::Instance { object: b, .. }) => assert!(Arc::ptr_eq(a, b)),
+        _ => panic!("Expected two instances."),
+    }
+    // Scaled first, then moved
+    assert_close(moved.bounding_box().min(), vec3(8.0, -2.0, -2.0));
+    assert_close(moved.bounding_box().max(), vec3(12.0, 2.0, 2.0));
+}
+
+#[test]
+fn scene_rotations_apply_about_x_then_y_then_z() {
+    let source = "material white lambertian albedo=1,1,1\n\
+                  sphere center=0,1,0 radius=0.1 material=white rotate=90,90,0 translate=0,0,5";
+    let (_, world) = ray_tracing::scene::parse_scene(source, std::path::Path::new(".")).expect("Scene should parse.");
+    assert_eq!(world.len(), 1);
+    // +y turns to +z about x, and then to +x about y
+    assert_close(world[0].bounding_box().centroid(), vec3(1.0, 0.0, 5.0));
+}
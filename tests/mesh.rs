@@ -0,0 +1,105 @@
+// OBJ files must load into the triangles they describe, and broken files must give errors
+// that point at the offending line.
+
+use std::io;
+
+use glam::{vec2, vec3, Vec3};
+use ray_tracing::mesh::Mesh;
+
+mod common;
+use common::material;
+
+// Writes the source to a temporary OBJ file and loads it.
+fn load(name: &str, source: &str) -> io::Result<Mesh> {
+    let path = std::env::temp_dir().join(format!("ray_tracing_{}_{}.obj", name, std::process::id()));
+    std::fs::write(&path, source).expect("Temporary file should be writable.");
+    let mesh = Mesh::load_obj(&path, &material());
+    std::fs::remove_file(&path).ok();
+    mesh
+}
+
+#[test]
+fn polygons_become_triangle_fans() {
+    let source = "# a unit square and a triangle\n\
+                  o square\n\
+                  v 0 0 0\n\
+                  v 1 0 0\n\
+                  v 1 1 0\n\
+                  v 0 1 0 1.0\n\
+                  vt 0 0\n\
+                  vt 1 0\n\
+                  vt 1 1\n\
+                  vn 0 0 1\n\
+                  f 1/1/1 2/2/1 3/3/1 4//1\n\
+                  usemtl ignored\n\
+                  f 1 2 3  # trailing comment\n";
+    let mesh = load("fan", source).expect("OBJ should load.");
+    let triangles = mesh.triangles();
+    assert_eq!(triangles.len(), 3);
+
+    let fans = triangles.iter().filter(|triangle| triangle.normals.is_some()).collect::<Vec<_>>();
+    assert_eq!(fans.len(), 2);
+    let with_uvs = fans.iter().find(|triangle| triangle.uvs.is_some()).expect("First fan triangle has uvs.");
+    assert_eq!(with_uvs.vertices, [Vec3::ZERO, vec3(1.0, 0.0, 0.0), vec3(1.0, 1.0, 0.0)]);
+    assert_eq!(with_uvs.uvs, Some([vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 1.0)]));
+    assert_eq!(with_uvs.normals, Some([Vec3::Z; 3]));
+    // The last corner has no uv, so the second fan triangle has none either
+    assert!(fans.iter().any(|triangle| triangle.uvs.is_none()
+        && triangle.vertices == [Vec3::ZERO, vec3(1.0, 1.0, 0.0), vec3(0.0, 1.0, 0.0)]));
+}
+
+#[test]
+fn negative_indices_count_back_from_the_latest_vertex() {
+    let source = "v 0 0 0\n\
+                  v 1 0 0\n\
+                  v 0 1 0\n\
+                  f -3 -2 -1\n\
+                  v 5 5 5\n\
+                  f -4 -1 -2\n";
+    let mesh = load("negative", source).expect("OBJ should load.");
+    let mut vertices = mesh.triangles().iter().map(|triangle| triangle.vertices).collect::<Vec<_>>();
+    vertices.sort_by(|a, b| a[1].x.total_cmp(&b[1].x));
+    assert_eq!(vertices, [
+        [Vec3::ZERO, vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)],
+        [Vec3::ZERO, vec3(5.0, 5.0, 5.0), vec3(0.0, 1.0, 0.0)],
+    ]);
+}
+
+#[test]
+fn texture_coordinates_take_one_to_three_numbers() {
+    let source = "v 0 0 0\n\
+                  v 1 0 0\n\
+                  v 0 1 0\n\
+                  vt 0.25\n\
+                  vt 0.5 0.75\n\
+                  vt 1 0.5 0.125\n\
+                  f 1/1 2/2 3/3\n";
+    let mesh = load("uvs", source).expect("OBJ should load.");
+    assert_eq!(mesh.triangles()[0].uvs, Some([vec2(0.25, 0.0), vec2(0.5, 0.75), vec2(1.0, 0.5)]));
+}
+
+#[test]
+fn errors_name_the_line() {
+    for (source, expected) in [
+        ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n", ":4: vertex index 4 out of range (have 3)"),
+        ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -4 1 2\n", ":4: vertex index -4 out of range (have 3)"),
+        ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n", ":4: vertex index 0 out of range (have 3)"),
+        ("v 0 0 0\nv 1 0 0\nf 1 2\n", ":3: face needs at least 3 vertices, found 2"),
+        ("v 0 0\n", ":1: expected 3 numbers, found 2"),
+        ("\nv 0 zero 0\n", ":2: invalid number 'zero'"),
+        ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1/1 2/1 3/1\n", ":4: texture index 1 out of range (have 0)"),
+        ("vt\n", ":1: expected 1 to 3 numbers, found 0"),
+        ("vt 0 0 0 0\n", ":1: expected 1 to 3 numbers, found 4"),
+        ("vt 0 v\n", ":1: invalid number 'v'"),
+    ] {
+        let error = load("broken", source).err().expect("Broken OBJ should fail.");
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().ends_with(expected), "{}", error);
+    }
+}
+
+#[test]
+fn missing_file_is_not_found() {
+    let error = Mesh::load_obj("assets/no_such_mesh.obj", &material()).err().expect("Missing file should fail.");
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+}
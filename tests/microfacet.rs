@@ -0,0 +1,78 @@
+// The microfacet model must conserve energy, and its sampling must match its density.
+
+use std::f32::consts::PI;
+
+use glam::{vec3, Vec3};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+use ray_tracing::camera::Color;
+use ray_tracing::microfacet::Microfacet;
+
+const SAMPLES: usize = 200_000;
+
+fn uniform_hemisphere(rng: &mut SmallRng) -> Vec3 {
+    let z = rng.gen::<f32>();
+    let phi = 2.0 * PI * rng.gen::<f32>();
+    let r = (1.0 - z * z).sqrt();
+    vec3(r * phi.cos(), r * phi.sin(), z)
+}
+
+fn outgoing(degrees: f32) -> Vec3 {
+    let theta = degrees.to_radians();
+    vec3(theta.sin(), 0.0, theta.cos())
+}
+
+// Fraction of light from wo that is reflected, with uniform sampling.
+fn albedo_uniform(model: &Microfacet, wo: Vec3, rng: &mut SmallRng) -> Color {
+    let sum = (0..SAMPLES).map(|_| model.eval(wo, uniform_hemisphere(rng))).sum::<Color>();
+    sum / SAMPLES as f32 * 2.0 * PI
+}
+
+// The same with the model's own sampling.
+fn albedo_sampled(model: &Microfacet, wo: Vec3, rng: &mut SmallRng) -> Color {
+    let sum = (0..SAMPLES)
+        .map(|_| {
+            let wi = model.sample(wo, rng.gen(), rng.gen(), rng.gen());
+            let pdf = model.pdf(wo, wi);
+            if pdf > 0.0 { model.eval(wo, wi) / pdf } else { Color::ZERO }
+        })
+        .sum::<Color>();
+    sum / SAMPLES as f32
+}
+
+#[test]
+fn white_surfaces_conserve_energy() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    for metalness in [0.0, 1.0] {
+        for roughness in [0.1, 0.5, 1.0] {
+            for angle in [0.0, 45.0, 80.0] {
+                let model = Microfacet::new(Color::ONE, roughness, metalness);
+                let albedo = albedo_sampled(&model, outgoing(angle), &mut rng);
+                assert!(albedo.max_element() <= 1.02, "albedo {} for {:?} at {}", albedo, model, angle);
+                // Light scattering more than once between the microfacets is lost, which
+                // takes up to about two thirds of it on very rough metals.
+                let min_albedo = if roughness <= 0.5 { 0.7 } else { 0.25 };
+                assert!(albedo.min_element() >= min_albedo, "albedo {} for {:?} at {}", albedo, model, angle);
+            }
+        }
+    }
+}
+
+#[test]
+fn sampling_matches_density() {
+    let mut rng = SmallRng::seed_from_u64(2);
+    for (roughness, metalness) in [(0.3, 1.0), (0.6, 0.0), (0.8, 0.5)] {
+        for angle in [10.0, 60.0] {
+            let model = Microfacet::new(vec3(0.9, 0.6, 0.3), roughness, metalness);
+            let wo = outgoing(angle);
+            let uniform = albedo_uniform(&model, wo, &mut rng);
+            let sampled = albedo_sampled(&model, wo, &mut rng);
+            assert!((uniform - sampled).abs().max_element() < 0.02, "{} != {} for {:?} at {}", uniform, sampled, model, angle);
+
+            // Reflections below the surface are lost, so the density may integrate to less than one.
+            let total_density = (0..SAMPLES).map(|_| model.pdf(wo, uniform_hemisphere(&mut rng))).sum::<f32>()
+                / SAMPLES as f32 * 2.0 * PI;
+            assert!(total_density <= 1.02 && total_density > 0.7, "density integrates to {}", total_density);
+        }
+    }
+}
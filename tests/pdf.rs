@@ -0,0 +1,72 @@
+// Light sampling densities must integrate to one over all directions, and agree with the
+// directions they generate.
+
+use std::f32::consts::PI;
+
+use glam::{vec3, Vec3};
+use rand::prelude::*;
+use rand::rngs::SmallRng;
+use ray_tracing::camera::Color;
+use ray_tracing::hittable::Shape;
+use ray_tracing::material::Material;
+use ray_tracing::pdf::{Onb, Pdf};
+
+const SAMPLES: usize = 200_000;
+
+fn uniform_direction(rng: &mut SmallRng) -> Vec3 {
+    loop {
+        let v = vec3(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+        if v.length_squared() > 1e-6 && v.length_squared() < 1.0 {
+            return v.normalize();
+        }
+    }
+}
+
+// Monte Carlo estimate of the integral of the density over the sphere of directions.
+fn integral(pdf: &Pdf, rng: &mut SmallRng) -> f32 {
+    let sum = (0..SAMPLES).map(|_| pdf.value(uniform_direction(rng)) as f64).sum::<f64>();
+    (sum / SAMPLES as f64 * 4.0 * PI as f64) as f32
+}
+
+fn light() -> Material {
+    Material::DiffuseLight(Color::ONE)
+}
+
+#[test]
+fn densities_integrate_to_one() {
+    let mut rng = SmallRng::seed_from_u64(7);
+    let origin = vec3(0.0, 0.0, 0.0);
+    let shapes = [
+        Shape::new_sphere(vec3(0.0, 0.0, -3.0), 1.0, &light()),
+        Shape::new_quad(vec3(-1.0, 2.0, -1.0), vec3(2.0, 0.0, 0.0), vec3(0.0, 0.0, 2.0), &light()),
+        Shape::new_triangle(vec3(-1.0, -1.0, 2.0), vec3(1.0, -1.0, 2.0), vec3(0.0, 1.0, 2.0), &light()),
+    ];
+    for shape in &shapes {
+        let pdf = Pdf::Shapes { shapes: std::slice::from_ref(shape), origin };
+        let total = integral(&pdf, &mut rng);
+        assert!((total - 1.0).abs() < 0.05, "integral {}", total);
+    }
+
+    let cosine = Pdf::Cosine(Onb::new(vec3(1.0, 1.0, 0.0)));
+    let all_lights = Pdf::Shapes { shapes: &shapes, origin };
+    for pdf in [&cosine, &Pdf::Sphere, &all_lights, &Pdf::Mixture(&cosine, &all_lights)] {
+        let total = integral(pdf, &mut rng);
+        assert!((total - 1.0).abs() < 0.05, "integral {}", total);
+    }
+}
+
+#[test]
+fn generated_directions_have_density() {
+    let mut rng = SmallRng::seed_from_u64(3);
+    let origin = vec3(0.5, 0.2, 0.0);
+    let shapes = [
+        Shape::new_sphere(vec3(0.0, 0.0, -3.0), 1.0, &light()),
+        Shape::new_quad(vec3(-1.0, 2.0, -1.0), vec3(2.0, 0.0, 0.0), vec3(0.0, 0.0, 2.0), &light()),
+        Shape::new_triangle(vec3(-1.0, -1.0, 2.0), vec3(1.0, -1.0, 2.0), vec3(0.0, 1.0, 2.0), &light()),
+    ];
+    let pdf = Pdf::Shapes { shapes: &shapes, origin };
+    for _ in 0..1000 {
+        let direction = pdf.generate(&mut rng);
+        assert!(pdf.value(direction) > 0.0, "{} has no density", direction);
+    }
+}
@@ -0,0 +1,208 @@
+// Planes, disks, cylinders, cones and tori must report the right hits for rays whose
+// answers are known.
+
+use glam::{vec3, Affine3A, Vec3};
+use ray_tracing::bvh::Bvh;
+use ray_tracing::hittable::{Hittable, Shape};
+use ray_tracing::roots::{solve_cubic, solve_quadratic, solve_quartic};
+
+mod common;
+use common::{assert_close, hit, material, misses};
+
+#[test]
+fn plane_is_hit_from_both_sides() {
+    let plane = Shape::new_plane(vec3(0.0, 1.0, 0.0), Vec3::Y, &material());
+
+    let (from_above, _) = hit(&plane, vec3(3.0, 5.0, -7.0), vec3(0.0, -2.0, 0.0));
+    assert!((from_above.t - 2.0).abs() < 1e-5);
+    assert_close(from_above.point, vec3(3.0, 1.0, -7.0));
+    assert_close(from_above.normal, Vec3::Y);
+    assert!(from_above.front_face);
+
+    let (from_below, _) = hit(&plane, vec3(100.0, -1.0, 100.0), vec3(1.0, 1.0, 0.0));
+    assert_close(from_below.point, vec3(102.0, 1.0, 100.0));
+    assert_close(from_below.normal, -Vec3::Y);
+    assert!(!from_below.front_face);
+    assert!((0.0..=1.0).contains(&from_below.u) && (0.0..=1.0).contains(&from_below.v));
+
+    assert!(misses(&plane, vec3(0.0, 5.0, 0.0), Vec3::X));
+    assert!(misses(&plane, vec3(0.0, 5.0, 0.0), Vec3::Y));
+    assert!(!plane.bounding_box().is_bounded());
+}
+
+#[test]
+fn disk_is_hit_inside_its_radius() {
+    let disk = Shape::new_disk(vec3(0.0, 0.0, 2.0), Vec3::Z, 1.0, &material());
+
+    let (center, _) = hit(&disk, Vec3::ZERO, Vec3::Z);
+    assert_close(center.point, vec3(0.0, 0.0, 2.0));
+    assert_close(center.normal, -Vec3::Z);
+    assert!(!center.front_face);
+    assert!(center.v.abs() < 1e-5);
+
+    let (edge, _) = hit(&disk, vec3(0.9, 0.0, 5.0), -Vec3::Z);
+    assert!(edge.front_face);
+    assert!((edge.v - 0.9).abs() < 1e-5);
+    assert!(misses(&disk, vec3(1.1, 0.0, 5.0), -Vec3::Z));
+
+    let bbox = disk.bounding_box();
+    assert_close(bbox.max() - bbox.min(), vec3(2.0, 2.0, 0.0001));
+}
+
+#[test]
+fn cylinder_sides_and_caps() {
+    // Along the y axis from y = 0 to y = 2
+    let cylinder = Shape::new_cylinder(Vec3::ZERO, vec3(0.0, 2.0, 0.0), 0.5, &material());
+
+    let (side, _) = hit(&cylinder, vec3(-5.0, 1.5, 0.0), Vec3::X);
+    assert_close(side.point, vec3(-0.5, 1.5, 0.0));
+    assert_close(side.normal, -Vec3::X);
+    assert!(side.front_face);
+    assert!((side.v - 0.75).abs() < 1e-5);
+
+    let (top, _) = hit(&cylinder, vec3(0.2, 10.0, 0.1), -Vec3::Y);
+    assert_close(top.point, vec3(0.2, 2.0, 0.1));
+    assert_close(top.normal, Vec3::Y);
+
+    let (bottom, _) = hit(&cylinder, vec3(0.2, -10.0, 0.1), Vec3::Y);
+    assert_close(bottom.point, vec3(0.2, 0.0, 0.1));
+    assert_close(bottom.normal, -Vec3::Y);
+
+    // From the inside, the far wall faces away from the ray
+    let (inside, _) = hit(&cylinder, vec3(0.0, 1.0, 0.0), Vec3::Z);
+    assert_close(inside.point, vec3(0.0, 1.0, 0.5));
+    assert_close(inside.normal, -Vec3::Z);
+    assert!(!inside.front_face);
+
+    assert!(misses(&cylinder, vec3(-5.0, 2.5, 0.0), Vec3::X));
+    assert!(misses(&cylinder, vec3(-5.0, 1.0, 0.6), Vec3::X));
+
+    assert_close(cylinder.bounding_box().min(), vec3(-0.5, 0.0, -0.5));
+    assert_close(cylinder.bounding_box().max(), vec3(0.5, 2.0, 0.5));
+}
+
+#[test]
+fn tilted_cylinder_matches_rotated_cylinder() {
+    let direction = vec3(1.0, 1.0, 0.0).normalize();
+    let tilted = Shape::new_cylinder(Vec3::ZERO, 2.0 * direction, 0.5, &material());
+    let rotated = Shape::new_cylinder(Vec3::ZERO, vec3(0.0, 2.0, 0.0), 0.5, &material())
+        .transformed(Affine3A::from_rotation_z(-45f32.to_radians()));
+
+    for (orig, dir) in [(vec3(0.5, 0.5, 5.0), -Vec3::Z), (vec3(3.0, 0.0, 0.1), -Vec3::X), (vec3(5.0, 5.0, 0.0), -direction)] {
+        let (expected, _) = hit(&rotated, orig, dir);
+        let (actual, _) = hit(&tilted, orig, dir);
+        assert!((actual.t - expected.t).abs() < 1e-4);
+        assert_close(actual.normal, expected.normal);
+    }
+}
+
+#[test]
+fn cone_side_base_and_apex() {
+    // Apex at y = 1 above a base of radius 1: the side is at 45 degrees
+    let cone = Shape::new_cone(Vec3::ZERO, Vec3::Y, 1.0, &material());
+
+    let (side, _) = hit(&cone, vec3(-5.0, 0.5, 0.0), Vec3::X);
+    assert_close(side.point, vec3(-0.5, 0.5, 0.0));
+    assert_close(side.normal, vec3(-1.0, 1.0, 0.0).normalize());
+    assert!(side.front_face);
+
+    let (base, _) = hit(&cone, vec3(0.3, -3.0, 0.0), Vec3::Y);
+    assert_close(base.point, vec3(0.3, 0.0, 0.0));
+    assert_close(base.normal, -Vec3::Y);
+
+    let (from_above, _) = hit(&cone, vec3(0.0, 3.0, 0.0), -Vec3::Y);
+    assert_close(from_above.point, Vec3::Y);
+
+    // The other half of the double cone is not part of the shape
+    assert!(misses(&cone, vec3(-5.0, 1.5, 0.0), Vec3::X));
+    assert!(misses(&cone, vec3(-5.0, -0.5, 0.0), Vec3::X));
+
+    assert_close(cone.bounding_box().min(), vec3(-1.0, 0.0, -1.0));
+    assert_close(cone.bounding_box().max(), vec3(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn torus_is_hit_on_its_tube() {
+    // Lying in the xz plane, the tube spans 1..3 from the center
+    let torus = Shape::new_torus(Vec3::ZERO, Vec3::Y, 2.0, 1.0, &material());
+
+    let (outside, _) = hit(&torus, vec3(-10.0, 0.0, 0.0), Vec3::X);
+    assert_close(outside.point, vec3(-3.0, 0.0, 0.0));
+    assert_close(outside.normal, -Vec3::X);
+    assert!(outside.front_face);
+
+    // From the hole, the inner side of the tube faces the ray
+    let (from_hole, _) = hit(&torus, vec3(0.0, 0.0, 0.0), Vec3::X);
+    assert_close(from_hole.point, vec3(1.0, 0.0, 0.0));
+    assert_close(from_hole.normal, -Vec3::X);
+    assert!(from_hole.front_face);
+
+    // From inside the tube, the surface faces away
+    let (inside, _) = hit(&torus, vec3(-2.0, 0.0, 0.0), Vec3::Y);
+    assert_close(inside.point, vec3(-2.0, 1.0, 0.0));
+    assert_close(inside.normal, -Vec3::Y);
+    assert!(!inside.front_face);
+
+    let (from_above, _) = hit(&torus, vec3(2.0, 10.0, 0.0), -Vec3::Y);
+    assert_close(from_above.point, vec3(2.0, 1.0, 0.0));
+    assert_close(from_above.normal, Vec3::Y);
+
+    // Grazing the top of the tube at an angle
+    let (slanted, _) = hit(&torus, vec3(2.0, 5.0, 5.0), vec3(0.0, -4.0, -5.0));
+    let p = slanted.point;
+    let ring_distance = (vec3(p.x, 0.0, p.z).length() - 2.0).hypot(p.y);
+    assert!((ring_distance - 1.0).abs() < 1e-4);
+
+    assert!(misses(&torus, vec3(0.0, 10.0, 0.0), -Vec3::Y));
+    assert!(misses(&torus, vec3(-10.0, 1.1, 0.0), Vec3::X));
+
+    assert_close(torus.bounding_box().min(), vec3(-3.0, -1.0, -3.0));
+    assert_close(torus.bounding_box().max(), vec3(3.0, 1.0, 3.0));
+}
+
+#[test]
+fn bvh_tests_unbounded_shapes_for_every_ray() {
+    let world = Bvh::new(vec![
+        Shape::new_sphere(vec3(0.0, 1.0, 0.0), 1.0, &material()),
+        Shape::new_plane(Vec3::ZERO, Vec3::Y, &material()),
+        Shape::new_sphere(vec3(5.0, 1.0, 0.0), 1.0, &material()),
+    ]);
+    assert!(!world.bounding_box().is_bounded());
+
+    // Far away from the spheres
+    let (floor, _) = hit(&world, vec3(100.0, 1.0, 100.0), -Vec3::Y);
+    assert_close(floor.point, vec3(100.0, 0.0, 100.0));
+    // The sphere in front of the plane is closer
+    let (ball, _) = hit(&world, vec3(0.0, 10.0, 0.0), -Vec3::Y);
+    assert_close(ball.point, vec3(0.0, 2.0, 0.0));
+
+    let moved = Shape::new_plane(Vec3::ZERO, Vec3::Y, &material()).transformed(Affine3A::from_translation(Vec3::Y));
+    assert!(!moved.bounding_box().is_bounded());
+    assert_close(hit(&moved, vec3(7.0, 5.0, 7.0), -Vec3::Y).0.point, vec3(7.0, 1.0, 7.0));
+}
+
+fn assert_roots(actual: Vec<f64>, expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len(), "{:?} != {:?}", actual, expected);
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn polynomial_roots() {
+    assert_roots(solve_quadratic(2.0, -2.0, -4.0), &[-1.0, 2.0]);
+    assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+    assert_roots(solve_quadratic(0.0, 2.0, -1.0), &[0.5]);
+
+    // (x - 1)(x - 2)(x + 3)
+    assert_roots(solve_cubic(1.0, 0.0, -7.0, 6.0), &[-3.0, 1.0, 2.0]);
+    // (x - 2)(x^2 + 1)
+    assert_roots(solve_cubic(2.0, -4.0, 2.0, -4.0), &[2.0]);
+
+    // (x + 2)(x - 0.5)(x - 1)(x - 4)
+    assert_roots(solve_quartic(1.0, -3.5, -4.5, 11.0, -4.0), &[-2.0, 0.5, 1.0, 4.0]);
+    // (x^2 - 9)(x^2 + 1)
+    assert_roots(solve_quartic(3.0, 0.0, -24.0, 0.0, -27.0), &[-3.0, 3.0]);
+    // x^4 + 1
+    assert_roots(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[]);
+}
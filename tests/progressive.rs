@@ -0,0 +1,83 @@
+// Progressive renders must stop sampling a pixel once it reaches the noise target, and only
+// then.
+
+use glam::{vec3, Vec3};
+use ray_tracing::background::Background;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::{Camera, CameraBuilder, Color};
+use ray_tracing::hittable::Shape;
+use ray_tracing::material::Material;
+use ray_tracing::progressive::Convergence;
+
+const WIDTH: u32 = 16;
+const HEIGHT: u32 = 8;
+const PIXELS: u64 = (WIDTH * HEIGHT) as u64;
+const LIMIT: u32 = 64;
+
+fn camera(background: Background) -> Camera {
+    CameraBuilder::default()
+        .set_image_width(WIDTH)
+        .set_aspect_ratio(2.0)
+        .set_samples_per_pixel(LIMIT)
+        .set_max_depth(8)
+        .set_background(background)
+        .build()
+}
+
+// Diffuse ground under the sky, noisy at a few samples
+fn noisy_world() -> Bvh<Shape> {
+    Bvh::new(vec![
+        Shape::new_sphere(vec3(0.0, -100.5, -1.0), 100.0, &Material::Lambertian(Color::splat(0.5).into())),
+        Shape::new_sphere(vec3(0.0, 0.0, -1.0), 0.5, &Material::Lambertian(Vec3::X.into())),
+    ])
+}
+
+fn convergence(target_noise: Option<f32>) -> Convergence {
+    Convergence { samples_per_pass: 4, min_samples: 8, target_noise, time_budget: None }
+}
+
+// Samples taken in every pass and the pixels left sampling after it
+fn passes(camera: &Camera, world: &Bvh<Shape>, convergence: &Convergence) -> Vec<(u64, usize)> {
+    let mut passes = Vec::new();
+    let mut previous_total = 0;
+    camera.render_progressive(world, convergence, |report| {
+        assert_eq!(report.pass as usize, passes.len());
+        passes.push((report.total_samples - previous_total, report.active_pixels));
+        previous_total = report.total_samples;
+    });
+    passes
+}
+
+#[test]
+fn noiseless_pixels_stop_after_the_minimum_samples() {
+    // Every sample of a plain background is the same, so there is no noise at all
+    let camera = camera(Background::Solid(Color::splat(0.5)));
+    let passes = passes(&camera, &Bvh::new(vec![]), &convergence(Some(0.001)));
+    assert_eq!(passes, [(4 * PIXELS, WIDTH as usize * HEIGHT as usize), (4 * PIXELS, 0)]);
+}
+
+#[test]
+fn without_a_target_every_pixel_reaches_the_limit() {
+    let camera = camera(Background::sky());
+    let passes = passes(&camera, &noisy_world(), &convergence(None));
+    assert_eq!(passes.len(), (LIMIT / 4) as usize);
+    assert_eq!(passes.iter().map(|(samples, _)| samples).sum::<u64>(), LIMIT as u64 * PIXELS);
+    assert_eq!(passes.last().unwrap().1, 0);
+}
+
+#[test]
+fn noisy_pixels_sample_until_the_target() {
+    let camera = camera(Background::sky());
+    let passes = passes(&camera, &noisy_world(), &convergence(Some(0.05)));
+    let total = passes.iter().map(|(samples, _)| samples).sum::<u64>();
+    // The smooth sky converges early, the diffuse ground takes longer
+    assert!(total > 8 * PIXELS && total < LIMIT as u64 * PIXELS, "{} samples", total);
+    assert!(passes.len() > 2, "{} passes", passes.len());
+    // Once converged, pixels are not sampled again
+    for window in passes.windows(2) {
+        let ((_, active_before), (samples, active_after)) = (window[0], window[1]);
+        assert!(active_after <= active_before);
+        assert!(samples <= 4 * active_before as u64, "{} samples for {} active pixels", samples, active_before);
+    }
+    assert_eq!(passes.last().unwrap().1, 0);
+}
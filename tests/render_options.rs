@@ -0,0 +1,142 @@
+// Progress reports and cancellation of Camera::render_with.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rand::Rng;
+use ray_tracing::aabb::Aabb;
+use ray_tracing::bvh::Bvh;
+use ray_tracing::camera::Camera;
+use ray_tracing::hittable::{HitRecord, Hittable, Shape};
+use ray_tracing::interval::Interval;
+use ray_tracing::material::Material;
+use ray_tracing::ray::Ray;
+use ray_tracing::render::{CancelToken, RenderError, RenderOptions, RenderProgress};
+use ray_tracing::tiles::Checkpoint;
+
+mod common;
+
+fn scene() -> (Camera, Bvh<Shape>) {
+    let (camera, world) = common::tiled_scene();
+    (camera.build(), world)
+}
+
+// Runs on a single thread, so that tiles are rendered one after another.
+fn one_thread<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("Should be able to create a thread pool.")
+        .install(f)
+}
+
+// Cancels the render after a number of hit tests, counting them.
+struct CancelAfter<'a> {
+    world: &'a Bvh<Shape>,
+    hits: AtomicUsize,
+    limit: usize,
+    cancel: CancelToken,
+}
+
+impl Hittable for CancelAfter<'_> {
+    fn hit<R: Rng>(&self, ray: &Ray, interval: Interval, rng: &mut R) -> Option<(HitRecord, &Material)> {
+        if self.hits.fetch_add(1, Ordering::Relaxed) + 1 == self.limit {
+            self.cancel.cancel();
+        }
+        self.world.hit(ray, interval, rng)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.world.bounding_box()
+    }
+}
+
+#[test]
+fn progress_is_reported_for_every_tile() {
+    let (camera, world) = scene();
+    let reports = Mutex::new(Vec::<RenderProgress>::new());
+    let options = RenderOptions {
+        on_progress: Some(Box::new(|progress: &RenderProgress| reports.lock().unwrap().push(*progress))),
+        ..RenderOptions::default()
+    };
+    let image = camera.render_with(&world, &options).expect("Render should finish.");
+    drop(options);
+    assert_eq!(image, camera.render(&world));
+
+    let reports = reports.into_inner().unwrap();
+    // One report before the first tile, then one per tile
+    assert_eq!(reports.len(), 7);
+    assert_eq!(reports[0].tiles_done, 0);
+    for (previous, next) in reports.iter().zip(&reports[1..]) {
+        assert_eq!(next.tiles_done, previous.tiles_done + 1);
+        assert!(next.pixels_done > previous.pixels_done);
+    }
+    let last = reports.last().unwrap();
+    assert_eq!((last.tiles_done, last.tiles_total), (6, 6));
+    assert_eq!((last.pixels_done, last.pixels_total), (96 * 54, 96 * 54));
+    assert_eq!(last.eta, Some(Duration::ZERO));
+}
+
+#[test]
+fn cancelled_render_resumes_from_checkpoint() {
+    let (camera, world) = scene();
+    let path = std::env::temp_dir().join(format!("ray_tracing_cancel_{}.ckpt", std::process::id()));
+    // A long interval, cancelling saves anyway
+    let checkpoint = Checkpoint::new(&path);
+
+    let cancel = CancelToken::new();
+    let options = RenderOptions {
+        on_progress: Some(Box::new(|progress: &RenderProgress| {
+            if progress.tiles_done == 2 {
+                cancel.cancel();
+            }
+        })),
+        cancel: Some(cancel.clone()),
+        checkpoint: Some(checkpoint.clone()),
+    };
+    let result = one_thread(|| camera.render_with(&world, &options));
+    assert!(matches!(result, Err(RenderError::Cancelled)));
+    assert!(path.exists(), "A cancelled render keeps its progress.");
+
+    let reports = Mutex::new(Vec::<RenderProgress>::new());
+    let options = RenderOptions {
+        on_progress: Some(Box::new(|progress: &RenderProgress| reports.lock().unwrap().push(*progress))),
+        checkpoint: Some(checkpoint.clone()),
+        ..RenderOptions::default()
+    };
+    let resumed = camera.render_with(&world, &options).expect("Resuming should work.");
+    drop(options);
+    assert_eq!(reports.into_inner().unwrap()[0].tiles_done, 2, "Exactly the finished tiles are restored.");
+    assert_eq!(resumed, camera.render(&world));
+    assert!(!path.exists());
+}
+
+#[test]
+fn render_cancelled_before_the_first_tile_renders_nothing() {
+    let (camera, world) = scene();
+    let counting_world = CancelAfter { world: &world, hits: AtomicUsize::new(0), limit: usize::MAX, cancel: CancelToken::new() };
+    let cancel = CancelToken::new();
+    let options = RenderOptions {
+        on_progress: Some(Box::new(|_: &RenderProgress| cancel.cancel())),
+        cancel: Some(cancel.clone()),
+        ..RenderOptions::default()
+    };
+    let result = camera.render_with(&counting_world, &options);
+    assert!(matches!(result, Err(RenderError::Cancelled)));
+    assert_eq!(counting_world.hits.into_inner(), 0);
+}
+
+#[test]
+fn cancelling_stops_within_a_tile() {
+    let (camera, world) = scene();
+    let cancel = CancelToken::new();
+    let cancelling_world = CancelAfter { world: &world, hits: AtomicUsize::new(0), limit: 100, cancel: cancel.clone() };
+    let options = RenderOptions { cancel: Some(cancel), ..RenderOptions::default() };
+    let result = one_thread(|| camera.render_with(&cancelling_world, &options));
+    assert!(matches!(result, Err(RenderError::Cancelled)));
+    // Every sample tests at least one ray, so even the smallest tile (32 x 22 pixels at 4
+    // samples) takes more
+    let hits = cancelling_world.hits.into_inner();
+    assert!(hits < 32 * 22 * 4, "{} hit tests after cancelling", hits);
+}
@@ -0,0 +1,68 @@
+// Scene files with mistakes must fail with the line and a message saying what is wrong.
+
+use std::path::Path;
+
+use ray_tracing::scene::{parse_scene, SceneError};
+
+fn parse_error(source: &str) -> (usize, String) {
+    match parse_scene(source, Path::new(".")) {
+        Ok(_) => panic!("Scene should not parse:\n{}", source),
+        Err(SceneError::Parse { line, message }) => (line, message),
+        Err(error) => panic!("Expected a parse error, got {}", error),
+    }
+}
+
+#[test]
+fn errors_carry_the_line_number() {
+    let source = "# comment\n\
+                  \n\
+                  material white lambertian albedo=1,1,1\n\
+                  sphere center=0,0,0 radius=1 material=grey  # comment\n";
+    assert_eq!(parse_error(source), (4, "unknown material 'grey'".to_string()));
+
+    let error = parse_scene("camera width=100\nlight", Path::new(".")).err().expect("Scene should not parse.");
+    assert_eq!(error.to_string(), "line 2: unknown statement 'light'");
+}
+
+#[test]
+fn malformed_statements_are_rejected() {
+    for (source, expected) in [
+        ("sphere center=0,0,0 material=white", "sphere is missing 'radius'"),
+        ("sphere center=0,0 radius=1", "center: expected x,y,z but found '0,0'"),
+        ("sphere center=0,0,0 radius=one", "radius: invalid number 'one'"),
+        ("sphere radius=1 radius=2", "'radius' is given more than once"),
+        ("material white lambertian albedo=1,1,1 extra", "unexpected 'extra' after parameters"),
+        ("material white lambertian albedo=1,1,1 shine=2", "unknown parameter 'shine' for material"),
+        ("material white plastic", "unknown material 'plastic' (expected lambertian, metal, dielectric, pbr or light)"),
+        ("camera from=0,0,1", "camera needs both 'from' and 'at'"),
+        ("camera shutter=1,0", "shutter: expected min,max but found '1,0'"),
+    ] {
+        assert_eq!(parse_error(source), (1, expected.to_string()), "{}", source);
+    }
+}
+
+#[test]
+fn camera_settings_must_make_sense() {
+    for (source, expected) in [
+        ("camera width=0", "width: expected a positive whole number but found '0'"),
+        ("camera width=-100", "width: invalid whole number '-100'"),
+        ("camera samples=0", "samples: expected a positive whole number but found '0'"),
+        ("camera aspect=0", "aspect: expected a positive number but found '0'"),
+        ("camera aspect=-16/9", "aspect: expected a positive number but found '-16/9'"),
+        ("camera aspect=1/0", "aspect: expected a positive number but found '1/0'"),
+        ("camera depth=-1", "depth: invalid whole number '-1'"),
+        ("camera vfov=-20", "vfov: expected an angle between 0 and 180 degrees but found '-20'"),
+        ("camera vfov=0", "vfov: expected an angle between 0 and 180 degrees but found '0'"),
+        ("camera vfov=180", "vfov: expected an angle between 0 and 180 degrees but found '180'"),
+        ("camera from=1,2,3 at=1,2,3", "camera 'from' and 'at' must be different points"),
+        ("camera from=0,5,0 at=0,0,0", "camera cannot look straight up or down"),
+        ("camera from=1,0,1 at=1,-2,1", "camera cannot look straight up or down"),
+        ("camera defocus=1 focus=0", "focus: expected a positive number but found '0'"),
+        ("camera defocus=1 focus=-2", "focus: expected a positive number but found '-2'"),
+    ] {
+        assert_eq!(parse_error(&format!("\n{}", source)), (2, expected.to_string()), "{}", source);
+    }
+
+    // Zero depth is allowed, if dark
+    parse_scene("camera width=10 aspect=16/9 samples=1 depth=0 vfov=179", Path::new(".")).expect("Scene should parse.");
+}
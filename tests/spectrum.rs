@@ -0,0 +1,44 @@
+// Spectral rendering must give back RGB colors on average, and indices of refraction must
+// match published values.
+
+use ray_tracing::camera::Color;
+use ray_tracing::material::Ior;
+use ray_tracing::spectrum::{rgb_at_wavelength, MAX_WAVELENGTH, MIN_WAVELENGTH};
+
+// Average over evenly spread wavelengths, like many uniformly drawn samples.
+fn average_over_wavelengths(color: Color) -> Color {
+    let n = 4000;
+    let sum = (0..n)
+        .map(|i| MIN_WAVELENGTH + (i as f32 + 0.5) / n as f32 * (MAX_WAVELENGTH - MIN_WAVELENGTH))
+        .map(|wavelength| rgb_at_wavelength(color, wavelength))
+        .sum::<Color>();
+    sum / n as f32
+}
+
+#[test]
+fn wavelength_samples_average_to_the_color() {
+    for color in [Color::ONE, Color::new(1.0, 0.0, 0.0), Color::new(0.1, 0.5, 0.9), Color::new(2.0, 3.0, 0.5)] {
+        let average = average_over_wavelengths(color);
+        assert!((average - color).abs().max_element() < 1e-2 * color.max_element(), "{} != {}", average, color);
+    }
+}
+
+#[test]
+fn black_stays_black() {
+    assert_eq!(rgb_at_wavelength(Color::ZERO, 550.0), Color::ZERO);
+}
+
+#[test]
+fn indices_of_refraction() {
+    // Published values at the sodium D line
+    assert!((Ior::BK7.at(Some(587.6)) - 1.5168).abs() < 1e-3);
+    assert!((Ior::DIAMOND.at(Some(587.6)) - 2.417).abs() < 2e-3);
+    // Without a wavelength, as in RGB renders
+    assert_eq!(Ior::BK7.at(None), Ior::BK7.at(Some(Ior::NOMINAL_WAVELENGTH)));
+    assert_eq!(Ior::Constant(1.33).at(Some(400.0)), 1.33);
+
+    // Normal dispersion: blue light bends more than red
+    for ior in [Ior::BK7, Ior::DIAMOND, Ior::Cauchy { a: 1.5, b: 0.02 }] {
+        assert!(ior.at(Some(450.0)) > ior.at(Some(650.0)), "{:?}", ior);
+    }
+}
@@ -0,0 +1,38 @@
+// Tone mapping must gamma encode and clamp finite colors, and count and blacken pixels
+// holding NaN or infinity instead of writing garbage.
+
+use ray_tracing::camera::Color;
+use ray_tracing::image::Image;
+use ray_tracing::tonemap::ToneMap;
+
+#[test]
+fn finite_colors_are_encoded_and_clamped() {
+    let tone_map = ToneMap::default();
+    assert_eq!(tone_map.map(&Color::new(0.25, 1.0, 0.0)), Some(Color::new(0.5, 1.0, 0.0)));
+    assert_eq!(tone_map.map(&Color::new(-1.0, 4.0, 1e30)), Some(Color::new(0.0, 1.0, 1.0)));
+    assert_eq!(tone_map.unmap(&Color::new(0.5, 1.0, 0.0)), Color::new(0.25, 1.0, 0.0));
+}
+
+#[test]
+fn nan_and_infinite_pixels_are_counted_and_black() {
+    let image = Image::from_pixels(3, 2, vec![
+        Color::new(0.25, 0.25, 0.25),
+        Color::new(f32::NAN, 0.5, 0.5),
+        Color::new(1.0, f32::INFINITY, 1.0),
+        Color::new(1.0, 1.0, f32::NEG_INFINITY),
+        Color::ONE,
+        Color::splat(f32::NAN),
+    ]);
+    let tone_map = ToneMap::default();
+    assert_eq!(tone_map.map(&image.pixels()[1]), None);
+
+    let quantized = tone_map.quantize(&image);
+    assert_eq!(quantized.invalid_pixels, 4);
+    assert_eq!(quantized.rgb, [
+        128, 128, 128, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 255, 255, 255, 0, 0, 0,
+    ]);
+
+    let clean = Image::from_pixels(1, 1, vec![Color::ONE]);
+    assert_eq!(tone_map.quantize(&clean).invalid_pixels, 0);
+}